@@ -0,0 +1,505 @@
+use std::collections::HashMap;
+
+use crate::builtin::Builtin;
+use crate::lexer::Lexer;
+use crate::lint::Linter;
+use crate::node::Node;
+use crate::op::Op;
+use crate::parser::Parser;
+use crate::token::{TT, Token};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Function,
+    Parameter,
+    LoopVariable,
+    CaughtError,
+    MatchBinding,
+    Builtin
+}
+
+/// A name declared in a program, or one of the builtins every program starts with.
+pub struct Symbol {
+    name: String,
+    kind: SymbolKind,
+    /// The name where it's declared, builtins have none.
+    token: Option<Token>,
+    /// Type of the value when it's known without running the program.
+    type_name: Option<&'static str>,
+    /// `fn name(a, b)` for functions.
+    signature: Option<String>,
+    doc: Option<String>,
+    /// Chars in which the name refers to this symbol.
+    scope: (usize, usize),
+    /// The function the symbol is declared in.
+    parent: Option<usize>
+}
+
+impl Symbol {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    pub fn get_token(&self) -> Option<&Token> {
+        self.token.as_ref()
+    }
+
+    pub fn get_doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
+    pub fn get_parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// An error or warning the lexer, parser or linter reports, `start` and `end` are char indices.
+pub struct Diagnostic {
+    start: usize,
+    end: usize,
+    message: String,
+    warning: bool
+}
+
+impl Diagnostic {
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.warning
+    }
+}
+
+/// What can be learned about a program without running it, used by `kumi lsp`.
+///
+/// Names are resolved with the scoping the interpreter's symbol tables use: a function body gets a
+/// scope of its own, blocks and loops declare into the scope around them, while `catch` names and
+/// `match` bindings are only visible in their own body, like the [`Linter`] sees them.
+pub struct Analysis {
+    tokens: Vec<Token>,
+    symbols: Vec<Symbol>,
+    /// Every name that's read or declared, with the symbol it refers to.
+    references: Vec<(Token, usize)>,
+    diagnostics: Vec<Diagnostic>,
+    parsed: bool
+}
+
+impl Analysis {
+    pub fn new(text: &str) -> Self {
+        let mut analysis = Self { tokens: Vec::new(), symbols: Vec::new(), references: Vec::new(), diagnostics: Vec::new(), parsed: false };
+
+        let tokens = match Lexer::new(text).try_tokenize() {
+            Ok(tokens) => tokens,
+            Err((pos, err)) => {
+                analysis.diagnostics.push(Diagnostic { start: pos, end: pos + 1, message: err.to_string(), warning: false });
+                analysis.symbols = Resolver::new(&[]).symbols;
+                return analysis;
+            },
+        };
+
+        let mut resolver = Resolver::new(&tokens);
+        match Parser::new(&tokens).parse() {
+            Ok(node) => {
+                for warning in Linter::new(true).lint(&node) {
+                    analysis.diagnostics.push(Diagnostic {
+                        start: *warning.get_token().get_start(),
+                        end: *warning.get_token().get_end(),
+                        message: format!("[{}] {}", warning.get_lint().code(), warning.get_message()),
+                        warning: true
+                    });
+                }
+
+                resolver.resolve(&node);
+                analysis.parsed = true
+            },
+            Err(err) => analysis.diagnostics.push(Diagnostic {
+                start: *err.get_token().get_start(),
+                end: *err.get_token().get_end(),
+                message: err.get_error().to_string(),
+                warning: false
+            }),
+        }
+
+        analysis.symbols = resolver.symbols;
+        analysis.references = resolver.references;
+        analysis.tokens = tokens;
+        analysis
+    }
+
+    /// Whether the text parsed, otherwise only the builtins are known.
+    pub fn is_parsed(&self) -> bool {
+        self.parsed
+    }
+
+    pub fn get_diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn get_symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The token `index` is in or right after.
+    pub fn token_at(&self, index: usize) -> Option<&Token> {
+        self.tokens.iter().find(|token| token.get_start() <= &index && &index <= token.get_end() && token.get_start() != token.get_end())
+    }
+
+    /// The symbol the name at `index` refers to, from either its declaration or a use.
+    pub fn symbol_at(&self, index: usize) -> Option<(&Token, &Symbol)> {
+        self.references.iter()
+            .find(|(token, _)| token.get_start() <= &index && &index <= token.get_end())
+            .map(|(token, symbol)| (token, &self.symbols[*symbol]))
+    }
+
+    /// Symbols that can be referred to at `index`, an inner or later declaration hides the ones with its name.
+    pub fn visible_at(&self, index: usize) -> Vec<&Symbol> {
+        let mut visible: HashMap<&str, &Symbol> = HashMap::new();
+
+        for symbol in &self.symbols {
+            let declared = symbol.token.as_ref().is_none_or(|token| token.get_end() < &index);
+            if !declared || index < symbol.scope.0 || symbol.scope.1 < index {
+                continue
+            }
+
+            match visible.get(symbol.name.as_str()) {
+                Some(other) if other.scope.0 > symbol.scope.0 => {},
+                _ => {
+                    visible.insert(&symbol.name, symbol);
+                },
+            }
+        }
+
+        let mut visible: Vec<&Symbol> = visible.into_values().collect();
+        visible.sort_by(|a, b| a.name.cmp(&b.name));
+        visible
+    }
+
+    /// One line like `let x: int` or `fn square(x)` telling what the symbol is.
+    pub fn describe(&self, symbol: &Symbol) -> String {
+        let typed = |text: String| match symbol.type_name {
+            Some(type_name) => format!("{}: {}", text, type_name),
+            None => text,
+        };
+
+        match symbol.kind {
+            SymbolKind::Variable => typed(format!("let {}", symbol.name)),
+            SymbolKind::Function => symbol.signature.clone().unwrap_or_else(|| format!("fn {}", symbol.name)),
+            SymbolKind::Parameter => match symbol.parent.map(|parent| &self.symbols[parent].name) {
+                Some(function) => format!("{} (parameter of {})", symbol.name, function),
+                None => symbol.name.clone(),
+            },
+            SymbolKind::LoopVariable => typed(format!("for {}", symbol.name)),
+            SymbolKind::CaughtError => format!("catch {}: error", symbol.name),
+            SymbolKind::MatchBinding => symbol.name.clone(),
+            SymbolKind::Builtin => format!("builtin {}", symbol.name),
+        }
+    }
+}
+
+struct Scope {
+    names: HashMap<String, usize>,
+    declared: Vec<usize>,
+    /// End of the last token seen inside the scope.
+    end: usize
+}
+
+impl Scope {
+    fn new() -> Self {
+        Self { names: HashMap::new(), declared: Vec::new(), end: 0 }
+    }
+}
+
+struct Resolver<'t> {
+    tokens: &'t [Token],
+    scopes: Vec<Scope>,
+    symbols: Vec<Symbol>,
+    references: Vec<(Token, usize)>,
+    /// Names read inside functions before anything declared them, a later global may still do it.
+    unresolved: Vec<(String, Token)>,
+    functions: Vec<usize>
+}
+
+impl<'t> Resolver<'t> {
+    fn new(tokens: &'t [Token]) -> Self {
+        let mut root = Scope::new();
+        let symbols: Vec<Symbol> = Builtin::builtins().into_iter().enumerate().map(|(index, builtin)| {
+            root.names.insert(builtin.get_name().to_string(), index);
+            Symbol {
+                name: builtin.get_name().to_string(),
+                kind: SymbolKind::Builtin,
+                token: None,
+                type_name: Some("builtin"),
+                signature: None,
+                doc: None,
+                scope: (0, usize::MAX),
+                parent: None
+            }
+        }).collect();
+
+        Self { tokens, scopes: vec![root], symbols, references: Vec::new(), unresolved: Vec::new(), functions: Vec::new() }
+    }
+
+    fn resolve(&mut self, node: &Node) {
+        self.visit(node);
+
+        for (name, token) in std::mem::take(&mut self.unresolved) {
+            if let Some(&symbol) = self.scopes[0].names.get(&name) {
+                self.references.push((token, symbol))
+            }
+        }
+        self.leave(Some(usize::MAX));
+    }
+
+    fn visit(&mut self, node: &Node) {
+        if let Some(token) = node.first_token() {
+            self.touch(token)
+        }
+
+        match node {
+            Node::Number(_, _) | Node::Break(_) | Node::Continue(_) => {},
+            Node::GetVar(name, token) => self.read(name, token),
+            Node::NotOp(node, _) | Node::UnaryOp(node, _) | Node::BitNotOp(node, _) | Node::IsNone(node) |
+            Node::GetMember(node, _, _, _) | Node::Unwrap(node, _) | Node::Throw(node, _) => self.visit(node),
+            Node::BinOp(left, _, right, _) | Node::LogicOp(left, _, right, _) | Node::Index(left, right, _) => {
+                self.visit(left);
+                self.visit(right)
+            },
+            Node::DeclareVar(name, value, token) => {
+                self.visit(value);
+                let type_name = self.infer(value);
+                self.declare(name, SymbolKind::Variable, token, type_name);
+            },
+            Node::DeclareFn(name, params, body, doc, token) => {
+                let function = self.declare(name, SymbolKind::Function, token, Some("fn"));
+                self.symbols[function].signature = Some(format!("fn {}({})", name, params.join(", ")));
+                self.symbols[function].doc = doc.clone();
+
+                let index = self.index_of(token);
+                let param_tokens = index.map(|index| self.param_tokens(index)).unwrap_or_default();
+
+                self.functions.push(function);
+                self.scopes.push(Scope::new());
+                for (position, param) in params.iter().enumerate() {
+                    self.declare(param, SymbolKind::Parameter, param_tokens.get(position).copied().unwrap_or(token), None);
+                }
+                self.visit(body);
+                self.leave(index.and_then(|index| self.block_end(index)));
+                self.functions.pop();
+            },
+            Node::Range(start, end, step, _, _) => {
+                self.visit(start);
+                self.visit(end);
+                if let Some(step) = step {
+                    self.visit(step)
+                }
+            },
+            Node::List(items, _) => {
+                for item in items {
+                    self.visit(item)
+                }
+            },
+            Node::Call(callee, args, _) | Node::CallMethod(callee, _, args, _, _) => {
+                self.visit(callee);
+                for arg in args {
+                    self.visit(arg)
+                }
+            },
+            Node::Return(value, _) => {
+                if let Some(value) = value {
+                    self.visit(value)
+                }
+            },
+            Node::Try(body, catches, finally, _) => {
+                self.visit(body);
+
+                for catch in catches {
+                    let index = self.index_of(catch.get_token());
+
+                    self.scopes.push(Scope::new());
+                    self.declare(catch.get_name(), SymbolKind::CaughtError, catch.get_token(), Some("error"));
+                    self.visit(catch.get_body());
+                    self.leave(index.and_then(|index| self.block_end(index)));
+                }
+
+                if let Some(finally) = finally {
+                    self.visit(finally)
+                }
+            },
+            Node::Match(node, arms, _) => {
+                self.visit(node);
+
+                for arm in arms {
+                    self.scopes.push(Scope::new());
+                    for (name, token) in arm.get_pattern().get_names() {
+                        self.declare(name, SymbolKind::MatchBinding, token, None);
+                    }
+                    self.visit(arm.get_body());
+                    self.leave(None)
+                }
+            },
+            Node::If(condition, then, otherwise, _) => {
+                self.visit(condition);
+                self.visit(then);
+                if let Some(otherwise) = otherwise {
+                    self.visit(otherwise)
+                }
+            },
+            Node::For(name, iterable, body, token) => {
+                self.visit(iterable);
+
+                let type_name = match self.infer(iterable) {
+                    Some("range") => Some("int"),
+                    _ => None
+                };
+                self.declare(name, SymbolKind::LoopVariable, token, type_name);
+                self.visit(body)
+            },
+            Node::Statements(statements) => {
+                for statement in statements {
+                    self.visit(statement)
+                }
+            },
+        }
+    }
+
+    fn declare(&mut self, name: &str, kind: SymbolKind, token: &Token, type_name: Option<&'static str>) -> usize {
+        let index = self.symbols.len();
+        self.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            token: Some(token.clone()),
+            type_name,
+            signature: None,
+            doc: None,
+            scope: (*token.get_start(), usize::MAX),
+            parent: self.functions.last().copied()
+        });
+        self.references.push((token.clone(), index));
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.names.insert(name.to_string(), index);
+            scope.declared.push(index);
+        }
+        self.touch(token);
+
+        index
+    }
+
+    fn read(&mut self, name: &str, token: &Token) {
+        match self.lookup(name) {
+            Some(symbol) => self.references.push((token.clone(), symbol)),
+            None if !self.functions.is_empty() => self.unresolved.push((name.to_string(), token.clone())),
+            None => {},
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.names.get(name).copied())
+    }
+
+    fn touch(&mut self, token: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.end = scope.end.max(*token.get_end())
+        }
+    }
+
+    /// Closes the innermost scope, its names stay visible up to `end` or the last token seen in it.
+    fn leave(&mut self, end: Option<usize>) {
+        let scope = match self.scopes.pop() {
+            Some(scope) => scope,
+            None => return,
+        };
+
+        let end = scope.end.max(end.unwrap_or(0));
+        for symbol in scope.declared {
+            self.symbols[symbol].scope.1 = end
+        }
+        if let Some(parent) = self.scopes.last_mut() {
+            parent.end = parent.end.max(end)
+        }
+    }
+
+    fn index_of(&self, token: &Token) -> Option<usize> {
+        self.tokens.binary_search_by_key(token.get_start(), |token| *token.get_start()).ok()
+    }
+
+    /// Parameter names between the parentheses after the function name at `index`.
+    fn param_tokens(&self, index: usize) -> Vec<&'t Token> {
+        self.tokens[index..].iter()
+            .skip_while(|token| !matches!(token.get_tt(), TT::LPR))
+            .take_while(|token| !matches!(token.get_tt(), TT::RPR))
+            .filter(|token| matches!(token.get_tt(), TT::Indenifier(_)))
+            .collect()
+    }
+
+    /// End of the `}` closing the first block that opens at or after the token at `index`.
+    fn block_end(&self, index: usize) -> Option<usize> {
+        let mut depth = 0;
+        for token in self.tokens[index..].iter().skip_while(|token| !matches!(token.get_tt(), TT::LBR)) {
+            match token.get_tt() {
+                TT::LBR => depth += 1,
+                TT::RBR if depth == 1 => return Some(*token.get_end()),
+                TT::RBR => depth -= 1,
+                _ => {},
+            }
+        }
+
+        None
+    }
+
+    /// Type of the value `node` produces, when it's clear without running it.
+    fn infer(&self, node: &Node) -> Option<&'static str> {
+        match node {
+            Node::Number(value, _) => Some(value.get_type_name()),
+            Node::List(_, _) => Some("list"),
+            Node::Range(_, _, _, _, _) => Some("range"),
+            Node::NotOp(_, _) | Node::LogicOp(_, _, _, _) | Node::IsNone(_) => Some("bool"),
+            Node::UnaryOp(node, _) | Node::BitNotOp(node, _) => self.infer(node),
+            Node::GetVar(name, _) => self.lookup(name).and_then(|symbol| self.symbols[symbol].type_name),
+            Node::BinOp(left, op, right, _) => match (self.infer(left), op, self.infer(right)) {
+                (Some("float"), _, Some("float")) => Some("float"),
+                (_, Op::Devd, _) => None,
+                (Some("int"), Op::Add | Op::Sub | Op::Mult, Some("float")) |
+                (Some("float"), Op::Add | Op::Sub | Op::Mult, Some("int")) => Some("float"),
+                (left, _, right) if left == right => left,
+                _ => None
+            },
+            Node::Call(callee, _, _) => match callee.as_ref() {
+                Node::GetVar(name, _) => match self.lookup(name).map(|symbol| &self.symbols[symbol]) {
+                    Some(symbol) if symbol.kind == SymbolKind::Builtin => Builtin::builtins().into_iter()
+                        .find(|builtin| builtin.get_name() == symbol.name)
+                        .map(|builtin| builtin.get_result_type()),
+                    _ => None
+                },
+                _ => None
+            },
+            Node::CallMethod(receiver, name, _, _, _) => match name.as_str() {
+                "len" => Some("int"),
+                "contains" => Some("bool"),
+                "rev" => self.infer(receiver),
+                _ => None
+            },
+            Node::Index(value, index, _) if matches!(index.as_ref(), Node::Range(_, _, _, _, _)) => self.infer(value),
+            Node::If(_, then, Some(otherwise), _) => match (self.infer(then), self.infer(otherwise)) {
+                (then, otherwise) if then == otherwise => then,
+                _ => None
+            },
+            Node::Statements(statements) => statements.last().and_then(|statement| self.infer(statement)),
+            _ => None
+        }
+    }
+}
\ No newline at end of file
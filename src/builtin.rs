@@ -0,0 +1,155 @@
+use std::io;
+use std::rc::Rc;
+
+use crate::error_code::ErrorCode;
+use crate::error_value::ErrorValue;
+use crate::decimal::Decimal;
+use crate::integer::Integer;
+use crate::rational::Rational;
+use crate::types::Type;
+
+/// Functions implemented by the interpreter itself and declared in every program.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    Int,
+    Float,
+    Rational,
+    Decimal,
+    Error,
+    Ok,
+    Err,
+    List
+}
+
+impl Builtin {
+    pub fn builtins() -> Vec<Self> {
+        vec![
+            Builtin::Int,
+            Builtin::Float,
+            Builtin::Rational,
+            Builtin::Decimal,
+            Builtin::Error,
+            Builtin::Ok,
+            Builtin::Err,
+            Builtin::List
+        ]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Builtin::Int => "int",
+            Builtin::Float => "float",
+            Builtin::Rational => "rational",
+            Builtin::Decimal => "decimal",
+            Builtin::Error => "error",
+            Builtin::Ok => "ok",
+            Builtin::Err => "err",
+            Builtin::List => "list",
+        }
+    }
+
+    /// Type name of the values the builtin returns.
+    pub fn get_result_type(&self) -> &'static str {
+        match self {
+            Builtin::Int => "int",
+            Builtin::Float => "float",
+            Builtin::Rational => "rational",
+            Builtin::Decimal => "decimal",
+            Builtin::Error => "error",
+            Builtin::Ok => "ok",
+            Builtin::Err => "err",
+            Builtin::List => "list",
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Builtin::Error => 2,
+            _ => 1
+        }
+    }
+
+    pub fn call(&self, mut args: Vec<Type>) -> io::Result<Type> {
+        if args.len() != self.arity() {
+            return Err(ErrorCode::ArgumentCount.error(
+                format!("{} takes {} argument(s) but {} were given", self.get_name(), self.arity(), args.len())
+            ));
+        }
+
+        let value = args.remove(0);
+        match self {
+            Builtin::Int => to_int(value),
+            Builtin::Float => to_float(value),
+            Builtin::Rational => to_rational(value),
+            Builtin::Decimal => to_decimal(value),
+            Builtin::Error => make_error(value, args.remove(0)),
+            Builtin::Ok => Ok(Type::Ok(Box::new(value))),
+            Builtin::Err => Ok(Type::Err(Box::new(value))),
+            Builtin::List => Ok(Type::List(Rc::new(value.iter()?.collect()))),
+        }
+    }
+}
+
+fn to_int(value: Type) -> io::Result<Type> {
+    match value {
+        Type::Int(_) => Ok(value),
+        Type::Bool(b) => Ok(Type::Int(Integer::from(b as i128))),
+        Type::Float(f) => Integer::from_f64(f).map(Type::Int).ok_or_else(
+            || ErrorCode::InvalidConversion.error(format!("can\'t convert {} to int", value.to_string()))
+        ),
+        Type::Rational(ref r) => Ok(Type::Int(r.trunc())),
+        Type::Decimal(ref d) => d.trunc().map(Type::Int).ok_or_else(
+            || ErrorCode::InvalidConversion.error(format!("can\'t convert {} to int", value.to_string()))
+        ),
+        _ => Err(ErrorCode::InvalidConversion.error(format!("can\'t convert {} to int", value.to_string())))
+    }
+}
+
+fn to_float(value: Type) -> io::Result<Type> {
+    match value {
+        Type::Bool(b) => Ok(Type::Float(b as u8 as f64)),
+        _ => value.to_f64().map(Type::Float).ok_or_else(
+            || ErrorCode::InvalidConversion.error(format!("can\'t convert {} to float", value.to_string()))
+        )
+    }
+}
+
+/// Floats are converted exactly, `rational(0.1)` is the binary fraction closest to 0.1.
+fn to_rational(value: Type) -> io::Result<Type> {
+    let rational = match value {
+        Type::Rational(_) => return Ok(value),
+        Type::Int(ref i) => Some(Rational::from(i.clone())),
+        Type::Float(f) => Rational::from_f64(f),
+        Type::Decimal(ref d) => d.to_rational(),
+        _ => None
+    };
+
+    rational.map(Type::Rational).ok_or_else(
+        || ErrorCode::InvalidConversion.error(format!("can\'t convert {} to rational", value.to_string()))
+    )
+}
+
+/// Floats are converted through their shortest representation, `decimal(0.1)` is exactly 0.1.
+fn to_decimal(value: Type) -> io::Result<Type> {
+    let decimal = match value {
+        Type::Decimal(_) => return Ok(value),
+        Type::Int(ref i) => Some(Decimal::from(i.clone())),
+        Type::Float(f) => Decimal::from_f64(f),
+        Type::Rational(ref r) => Decimal::from_rational(r, 0),
+        _ => None
+    };
+
+    decimal.map(Type::Decimal).ok_or_else(
+        || ErrorCode::InvalidConversion.error(format!("can\'t convert {} to decimal", value.to_string()))
+    )
+}
+
+/// `error(kind, message)` makes an error value to `throw`, catchable with `catch kind e`.
+fn make_error(kind: Type, message: Type) -> io::Result<Type> {
+    match (&kind, &message) {
+        (Type::Str(kind), Type::Str(message)) => Ok(Type::Error(Rc::new(ErrorValue::new(kind, message)))),
+        _ => Err(ErrorCode::TypeMismatch.error(format!(
+            "can\'t make an error of {} and {}, both kind and message must be strings", kind.to_string(), message.to_string()
+        )))
+    }
+}
\ No newline at end of file
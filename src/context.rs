@@ -1,34 +1,141 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::io;
+use std::rc::Rc;
+
+use crate::error_value::Span;
+use crate::lexer::{get_line_by_char_index, get_position_by_char_index};
 use crate::symbol_table::SymbolTable;
+use crate::source::Source;
+use crate::token::Token;
+
+/// Frames shown at each end of a traceback, the ones in between are collapsed.
+const TRACEBACK_EDGE: usize = 10;
 
 #[derive(Clone)]
 pub struct Context<'c> {
     pos: usize,
+    depth: usize,
     name: String,
+    source: Rc<Source>,
     parent: Option<&'c Self>,
     symbol_table: SymbolTable<'c>
 }
 
 impl<'c> Context<'c> {
-    pub fn new(name: &str, parent: Option<&'c Self>, pos: usize) -> Self {
+    pub fn new(name: &str, parent: Option<&'c Self>, source: Rc<Source>, pos: usize) -> Self {
         let symbol_table = parent.map_or(SymbolTable::default(), |parent| SymbolTable::new(parent.get_symbol_table()));
-        Self { pos, name: name.to_string(), parent, symbol_table }
+        let depth = parent.map_or(0, |parent| parent.depth + 1);
+        Self { pos, depth, name: name.to_string(), source, parent, symbol_table }
+    }
+
+    /// The frame of a call, `scope` is where the function was declared and `caller` is only used for tracebacks.
+    pub fn call(name: &str, caller: &'c Self, scope: &'c SymbolTable<'c>, source: Rc<Source>) -> Self {
+        Self { pos: 0, depth: caller.depth + 1, name: name.to_string(), source, parent: Some(caller), symbol_table: SymbolTable::new(scope) }
+    }
+
+    /// Runs `body` in a scope of its own inside this frame, the names it declares are gone afterwards.
+    pub fn scoped<T>(&mut self, body: impl FnOnce(&mut Context) -> T) -> T {
+        let mut scope = Context {
+            pos: self.pos,
+            depth: self.depth,
+            name: self.name.clone(),
+            source: self.source.clone(),
+            parent: self.parent,
+            symbol_table: SymbolTable::new(&self.symbol_table)
+        };
+        let result = body(&mut scope);
+
+        // a failure in the body is reported where it happened
+        self.pos = scope.pos;
+        result
+    }
+
+    /// The scope with `id` in one of the active frames, the globals when that scope is gone.
+    pub fn find_scope(&self, id: usize) -> &SymbolTable<'c> {
+        let mut ctx = self;
+        loop {
+            if let Some(scope) = ctx.symbol_table.find(id) {
+                return scope;
+            }
+            match ctx.parent {
+                Some(parent) => ctx = parent,
+                None => return &ctx.symbol_table,
+            }
+        }
     }
 
-    pub fn step(self) -> Option<&'c Self> {
+    pub fn step(&self) -> Option<&'c Self> {
         self.parent
     }
 
+    /// Remembers where in its source this frame currently is, so a failure can be reported there.
+    pub fn set_pos(&mut self, token: &Token) {
+        self.pos = *token.get_start()
+    }
+
     pub fn generate_traceback(&self) -> String {
-        let mut traceback = String::new();
-        
-        let mut ctx = Some(self.clone());
+        let mut frames = Vec::new();
+
+        let mut ctx = Some(self);
         while let Some(context) = ctx {
-            traceback.push_str(&format!("\tLine {}, in {}\n", context.pos, context.name));
+            frames.push(context.frame());
+
+            ctx = context.step();
+        }
+
+        frames.reverse();
+
+        if frames.len() > TRACEBACK_EDGE * 2 {
+            let omitted = frames.len() - TRACEBACK_EDGE * 2;
+            frames.splice(TRACEBACK_EDGE..frames.len() - TRACEBACK_EDGE, [format!("\t... {} frames omitted\n", omitted)]);
+        }
 
-            ctx = context.step().map(|value| value.clone());
+        frames.concat()
+    }
+
+    /// Attaches the traceback of the active frames to `err`, unless an inner frame already did.
+    pub fn trace(&self, err: io::Error) -> io::Error {
+        if err.get_ref().is_some_and(|inner| inner.is::<Traceback>()) {
+            return err;
         }
 
-        traceback
+        io::Error::new(err.kind(), Traceback { traceback: self.generate_traceback(), span: self.span(), err })
+    }
+
+    /// Where in its source this frame currently is.
+    pub fn span(&self) -> Span {
+        let (line, col) = get_position_by_char_index(self.source.get_text(), self.pos);
+        Span::new(self.source.get_name(), line+1, col+1)
+    }
+
+    fn frame(&self) -> String {
+        let text = self.source.get_text();
+        let pos = self.pos.min(text.len());
+
+        let (line, col) = get_position_by_char_index(text, pos);
+        let code = get_line_by_char_index(text, pos).unwrap_or(&[]).iter().collect::<String>();
+
+        format!(
+            "\tFile \"{}\", line {}, column {}, in {}\n\t\t{}\n",
+            self.source.get_name(),
+            line+1,
+            col+1,
+            self.name,
+            code.trim()
+        )
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn get_source(&self) -> &Rc<Source> {
+        &self.source
+    }
+
+    pub fn set_source(&mut self, source: Rc<Source>) {
+        self.source = source
     }
 
     pub fn get_symbol_table_mut(&mut self) -> &mut SymbolTable<'c> {
@@ -38,4 +145,35 @@ impl<'c> Context<'c> {
     pub fn get_symbol_table(&self) -> &SymbolTable<'c> {
         &self.symbol_table
     }
-}
\ No newline at end of file
+}
+
+/// A runtime error together with the call stack that was active when it happened.
+pub struct Traceback {
+    traceback: String,
+    span: Span,
+    err: io::Error
+}
+
+impl Traceback {
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_error(&self) -> &io::Error {
+        &self.err
+    }
+}
+
+impl Display for Traceback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Traceback (most recent call last):\n{}{}", self.traceback, self.err)
+    }
+}
+
+impl Debug for Traceback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for Traceback {}
\ No newline at end of file
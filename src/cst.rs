@@ -0,0 +1,279 @@
+use std::fmt::{self, Debug};
+use std::io;
+
+use crate::lexer::Lexer;
+use crate::token::{TT, Token};
+
+/// Text the parser doesn't need, the lossless mode keeps it so the source can be reproduced.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TriviaKind {
+    /// Spaces, tabs and `\r`.
+    Whitespace,
+    /// A new line inside parentheses or square brackets, elsewhere new lines are tokens.
+    NewLine,
+    /// `# ...`, `/* ... */` and `////...` comments.
+    Comment
+}
+
+#[derive(Clone)]
+pub struct Trivia {
+    kind: TriviaKind,
+    text: String
+}
+
+impl Trivia {
+    pub fn get_kind(&self) -> TriviaKind {
+        self.kind
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Splits the text between two tokens, which the lexer accepted as skippable, into trivia.
+    fn split(text: &[char]) -> Vec<Self> {
+        let mut trivia = Vec::new();
+        let mut pos = 0;
+
+        while pos < text.len() {
+            let rest = &text[pos..];
+            let (kind, len) = match rest[0] {
+                '\n' => (TriviaKind::NewLine, 1),
+                '#' | '/' if !rest.starts_with(&['/', '*']) => (TriviaKind::Comment, rest.iter().position(|c| c == &'\n').unwrap_or(rest.len())),
+                '/' => (TriviaKind::Comment, block_comment_len(rest)),
+                _ => (TriviaKind::Whitespace, rest.iter().position(|c| !" \t\r".contains(*c)).unwrap_or(rest.len()).max(1)),
+            };
+
+            trivia.push(Self { kind, text: rest[..len].iter().collect() });
+            pos += len
+        }
+
+        trivia
+    }
+}
+
+/// Length of the nested `/* */` comment `text` starts with, or all of `text` if it isn't closed.
+fn block_comment_len(text: &[char]) -> usize {
+    let mut depth = 0;
+    let mut pos = 0;
+
+    while pos < text.len() {
+        match &text[pos..] {
+            ['/', '*', ..] => depth += 1,
+            ['*', '/', ..] => {
+                depth -= 1;
+                if depth == 0 {
+                    return pos + 2;
+                }
+            },
+            _ => {
+                pos += 1;
+                continue
+            },
+        }
+
+        pos += 2
+    }
+
+    text.len()
+}
+
+/// A token with its exact source text and the trivia in front of it.
+#[derive(Clone)]
+pub struct CstToken {
+    token: Token,
+    text: String,
+    leading: Vec<Trivia>
+}
+
+impl CstToken {
+    pub fn get_token(&self) -> &Token {
+        &self.token
+    }
+
+    pub fn get_tt(&self) -> &TT {
+        self.token.get_tt()
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_leading(&self) -> &[Trivia] {
+        &self.leading
+    }
+}
+
+/// Attaches the text between `tokens` to the token after it, the trivia at the end goes to EOF.
+pub fn attach_trivia(text: &[char], tokens: Vec<Token>) -> Vec<CstToken> {
+    let mut pos = 0;
+
+    tokens.into_iter().map(|token| {
+        let (start, end) = (*token.get_start(), *token.get_end());
+        let leading = Trivia::split(&text[pos.min(start)..start]);
+        pos = end;
+
+        CstToken { text: text[start..end].iter().collect(), token, leading }
+    }).collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SyntaxKind {
+    /// The whole source, statements separated by new line and `;` tokens and ended by EOF.
+    Root,
+    /// The tokens of one statement, blocks and parentheses inside it are nested nodes.
+    Statement,
+    /// `( ... )`.
+    Paren,
+    /// `[ ... ]`.
+    Bracket,
+    /// `{ ... }`, it holds statements like [`SyntaxKind::Root`].
+    Block
+}
+
+#[derive(Clone)]
+pub enum Element {
+    Token(CstToken),
+    Node(SyntaxNode)
+}
+
+/// A node of the concrete syntax tree, its tokens and trivia in order are exactly the source text.
+///
+/// The tree follows the brackets and statement separators only, expressions inside a statement are
+/// left flat, so even text the [`crate::parser::Parser`] rejects keeps its shape.
+#[derive(Clone)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<Element>
+}
+
+impl SyntaxNode {
+    /// Lexes `text` keeping the trivia and builds its tree.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut tokens = Lexer::new(text).tokenize_lossless()?.into_iter().peekable();
+        let mut children = statements(&mut tokens, false);
+        children.extend(tokens.map(Element::Token));
+
+        Ok(Self { kind: SyntaxKind::Root, children })
+    }
+
+    pub fn get_kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn get_children(&self) -> &[Element] {
+        &self.children
+    }
+
+    /// Tokens of the node and of all nodes inside it in source order.
+    pub fn tokens(&self) -> Vec<&CstToken> {
+        self.children.iter().flat_map(|child| match child {
+            Element::Token(token) => vec![token],
+            Element::Node(node) => node.tokens(),
+        }).collect()
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(f, "{}{:?}", "  ".repeat(depth), self.get_kind())?;
+
+        for child in self.get_children() {
+            match child {
+                Element::Node(node) => node.write_tree(f, depth + 1)?,
+                Element::Token(token) => {
+                    for trivia in token.get_leading() {
+                        writeln!(f, "{}{:?} {:?}", "  ".repeat(depth + 1), trivia.get_kind(), trivia.get_text())?;
+                    }
+                    writeln!(f, "{}Token {:?}", "  ".repeat(depth + 1), token.get_text())?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+type Tokens = std::iter::Peekable<std::vec::IntoIter<CstToken>>;
+
+/// Statements and separators up to EOF, or up to the `}` closing a block when `in_block`.
+fn statements(tokens: &mut Tokens, in_block: bool) -> Vec<Element> {
+    let mut children = Vec::new();
+
+    while let Some(token) = tokens.peek() {
+        match token.get_tt() {
+            TT::EOF => break,
+            TT::RBR if in_block => break,
+            TT::NL | TT::SEMI | TT::Doc(_) => children.push(Element::Token(tokens.next().unwrap())),
+            _ => children.push(Element::Node(statement(tokens, in_block))),
+        }
+    }
+
+    children
+}
+
+fn statement(tokens: &mut Tokens, in_block: bool) -> SyntaxNode {
+    let mut children = Vec::new();
+
+    while let Some(token) = tokens.peek() {
+        match token.get_tt() {
+            TT::EOF | TT::NL | TT::SEMI | TT::Doc(_) => break,
+            TT::RBR if in_block => break,
+            _ => children.push(element(tokens)),
+        }
+    }
+
+    SyntaxNode { kind: SyntaxKind::Statement, children }
+}
+
+/// The next token, or the whole group it opens.
+fn element(tokens: &mut Tokens) -> Element {
+    let open = match tokens.next() {
+        Some(token) => token,
+        None => return Element::Node(SyntaxNode { kind: SyntaxKind::Statement, children: Vec::new() }),
+    };
+
+    let kind = match open.get_tt() {
+        TT::LPR => SyntaxKind::Paren,
+        TT::LSQ => SyntaxKind::Bracket,
+        TT::LBR => SyntaxKind::Block,
+        _ => return Element::Token(open),
+    };
+
+    let mut children = vec![Element::Token(open)];
+    if kind == SyntaxKind::Block {
+        children.extend(statements(tokens, true));
+    } else {
+        while let Some(token) = tokens.peek() {
+            match (kind, token.get_tt()) {
+                (_, TT::EOF) | (SyntaxKind::Paren, TT::RSQ) | (SyntaxKind::Bracket, TT::RPR) | (_, TT::RBR) => break,
+                (SyntaxKind::Paren, TT::RPR) | (SyntaxKind::Bracket, TT::RSQ) => break,
+                _ => children.push(element(tokens)),
+            }
+        }
+    }
+
+    // an unclosed group simply ends where its closing bracket is missing
+    let closes = matches!(
+        (kind, tokens.peek().map(|token| token.get_tt())),
+        (SyntaxKind::Paren, Some(TT::RPR)) | (SyntaxKind::Bracket, Some(TT::RSQ)) | (SyntaxKind::Block, Some(TT::RBR))
+    );
+    if closes {
+        children.extend(tokens.next().map(Element::Token));
+    }
+
+    Element::Node(SyntaxNode { kind, children })
+}
+
+impl ToString for SyntaxNode {
+    /// The source text the tree was built from, trivia included.
+    fn to_string(&self) -> String {
+        self.tokens().iter().map(|token| {
+            token.leading.iter().map(|trivia| trivia.text.as_str()).collect::<String>() + &token.text
+        }).collect()
+    }
+}
+
+impl Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
\ No newline at end of file
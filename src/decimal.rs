@@ -0,0 +1,207 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::integer::Integer;
+use crate::rational::Rational;
+
+/// Digits kept after the point when a quotient doesn't terminate, like in `1d / 3d`.
+pub const DIVISION_SCALE: u64 = 28;
+
+/// Fixed-point number `value / 10^scale`.
+///
+/// The scale is kept from the literal, so `19.90d` prints with both digits. Sums keep the larger
+/// scale of the operands and products add them up. Operations return `None` when the result would be
+/// too large for an [`Integer`] or on division by zero.
+#[derive(Clone)]
+pub struct Decimal {
+    value: Integer,
+    scale: u64
+}
+
+impl Decimal {
+    /// Converts `rational` exactly when its decimal expansion terminates, otherwise rounds half to
+    /// even after [`DIVISION_SCALE`] digits. The result has at least `min_scale` digits after the point.
+    pub fn from_rational(rational: &Rational, min_scale: u64) -> Option<Self> {
+        let mut denominator = rational.get_denominator().clone();
+        let (mut twos, mut fives) = (0, 0);
+        while let Some((quotient, remainder)) = denominator.checked_div_rem(&Integer::from(2)) {
+            if !remainder.is_zero() {
+                break
+            }
+            denominator = quotient;
+            twos += 1;
+        }
+        while let Some((quotient, remainder)) = denominator.checked_div_rem(&Integer::from(5)) {
+            if !remainder.is_zero() {
+                break
+            }
+            denominator = quotient;
+            fives += 1;
+        }
+
+        let terminates = denominator == Integer::from(1);
+        let scale = if terminates { min_scale.max(twos).max(fives) } else { min_scale.max(DIVISION_SCALE) };
+
+        let numerator = rational.get_numerator().checked_mul(&Integer::pow10(scale)?)?;
+        let (quotient, remainder) = numerator.checked_div_rem(rational.get_denominator())?;
+
+        // round half to even, the quotient was truncated towards zero
+        let twice = remainder.abs().checked_mul(&Integer::from(2))?;
+        let value = match twice.cmp(rational.get_denominator()) {
+            Ordering::Greater => away_from_zero(&quotient, rational.is_negative()),
+            Ordering::Equal if !quotient.is_even() => away_from_zero(&quotient, rational.is_negative()),
+            _ => quotient,
+        };
+
+        Some(Self { value, scale })
+    }
+
+    /// Uses the shortest representation that reads back as `f`, so `decimal(0.1)` is `0.1`.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+
+        format!("{}", f).parse().ok()
+    }
+
+    pub fn to_rational(&self) -> Option<Rational> {
+        Rational::new(self.value.clone(), Integer::pow10(self.scale)?)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    /// Rounds towards zero.
+    pub fn trunc(&self) -> Option<Integer> {
+        self.value.checked_div(&Integer::pow10(self.scale)?)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self { value: -&self.value, scale: self.scale }
+    }
+
+    fn rescale(&self, scale: u64) -> Option<Integer> {
+        self.value.checked_mul(&Integer::pow10(scale - self.scale)?)
+    }
+
+    /// Values of both operands brought to the larger scale.
+    fn align(&self, other: &Self) -> Option<(Integer, Integer, u64)> {
+        let scale = self.scale.max(other.scale);
+        Some((self.rescale(scale)?, other.rescale(scale)?, scale))
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+        Some(Self { value: &a + &b, scale })
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.neg())
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(Self { value: self.value.checked_mul(&other.value)?, scale: self.scale.checked_add(other.scale)? })
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        let quotient = self.to_rational()?.checked_div(&other.to_rational()?)?;
+        Self::from_rational(&quotient, self.scale.max(other.scale))
+    }
+
+    /// Remainder of the division truncated towards zero, it has the sign of `self`.
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+        Some(Self { value: a.checked_rem(&b)?, scale })
+    }
+
+    /// A negative `exp` goes through [`Self::from_rational`] and may be rounded.
+    pub fn checked_pow(&self, exp: &Integer) -> Option<Self> {
+        if exp.is_negative() {
+            return Self::from_rational(&self.to_rational()?.checked_pow(exp)?, self.scale);
+        }
+
+        let scale = self.scale.checked_mul(exp.to_i128()? as u64)?;
+        Some(Self { value: self.value.checked_pow(exp)?, scale })
+    }
+}
+
+fn away_from_zero(value: &Integer, negative: bool) -> Integer {
+    value + &Integer::from(if negative { -1 } else { 1 })
+}
+
+impl From<Integer> for Decimal {
+    fn from(value: Integer) -> Self {
+        Self { value, scale: 0 }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    /// Parses digits with an optional `-`, a single `.` and an exponent such as `e-3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("{} isn\'t a decimal number", s);
+
+        let (mantissa, exponent) = s.split_once(['e', 'E']).unwrap_or((s, "0"));
+        let exponent: i64 = exponent.parse().map_err(|_| error())?;
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if fraction.starts_with('-') {
+            return Err(error());
+        }
+
+        let value: Integer = format!("{}{}", whole, fraction).parse().map_err(|_| error())?;
+        let scale = (fraction.len() as i64).checked_sub(exponent).ok_or_else(error)?;
+        if scale >= 0 {
+            return Ok(Self { value, scale: scale as u64 });
+        }
+
+        let value = value.checked_mul(&Integer::pow10(scale.unsigned_abs()).ok_or_else(error)?).ok_or_else(error)?;
+        Ok(Self { value, scale: 0 })
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.align(other) {
+            Some((a, b, _)) => a.cmp(&b),
+            None => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.value.abs().to_string();
+        let sign = if self.value.is_negative() { "-" } else { "" };
+        let scale = self.scale as usize;
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
\ No newline at end of file
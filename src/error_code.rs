@@ -0,0 +1,598 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::io;
+
+/// Stable identifiers of every error class kumi can report.
+///
+/// Codes are never renumbered or reused, new classes are appended to the end.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidCharacter,
+    MalformedNumber,
+    TypeMismatch,
+    IncompleteOperator,
+    UnexpectedEnd,
+    ExpectedExpression,
+    UnclosedParenthesis,
+    UnexpectedToken,
+    ExpectedIdentifier,
+    ExpectedAssignment,
+    DivisionByZero,
+    BadUnaryOperand,
+    UnsupportedOperator,
+    UndefinedVariable,
+    DeniedWarning,
+    ReturnOutsideFunction,
+    UnclosedBrace,
+    NotCallable,
+    ArgumentCount,
+    RecursionLimit,
+    InvalidConversion,
+    IntegerOverflow,
+    NegativeShift,
+    UnterminatedString,
+    InvalidEscape,
+    UncaughtError,
+    UnknownMember,
+    ExpectedCatch,
+    NoMatchingArm,
+    ZeroStep,
+    IndexOutOfRange,
+    BreakOutsideLoop,
+    UnterminatedComment,
+    Interrupted
+}
+
+impl ErrorCode {
+    fn codes() -> Vec<Self> {
+        vec![
+            ErrorCode::InvalidCharacter,
+            ErrorCode::MalformedNumber,
+            ErrorCode::TypeMismatch,
+            ErrorCode::IncompleteOperator,
+            ErrorCode::UnexpectedEnd,
+            ErrorCode::ExpectedExpression,
+            ErrorCode::UnclosedParenthesis,
+            ErrorCode::UnexpectedToken,
+            ErrorCode::ExpectedIdentifier,
+            ErrorCode::ExpectedAssignment,
+            ErrorCode::DivisionByZero,
+            ErrorCode::BadUnaryOperand,
+            ErrorCode::UnsupportedOperator,
+            ErrorCode::UndefinedVariable,
+            ErrorCode::DeniedWarning,
+            ErrorCode::ReturnOutsideFunction,
+            ErrorCode::UnclosedBrace,
+            ErrorCode::NotCallable,
+            ErrorCode::ArgumentCount,
+            ErrorCode::RecursionLimit,
+            ErrorCode::InvalidConversion,
+            ErrorCode::IntegerOverflow,
+            ErrorCode::NegativeShift,
+            ErrorCode::UnterminatedString,
+            ErrorCode::InvalidEscape,
+            ErrorCode::UncaughtError,
+            ErrorCode::UnknownMember,
+            ErrorCode::ExpectedCatch,
+            ErrorCode::NoMatchingArm,
+            ErrorCode::ZeroStep,
+            ErrorCode::IndexOutOfRange,
+            ErrorCode::BreakOutsideLoop,
+            ErrorCode::UnterminatedComment,
+            ErrorCode::Interrupted
+        ]
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_uppercase();
+        Self::codes().into_iter().find(|error_code| error_code.code() == code)
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidCharacter => "K0001",
+            ErrorCode::MalformedNumber => "K0002",
+            ErrorCode::TypeMismatch => "K0003",
+            ErrorCode::IncompleteOperator => "K0004",
+            ErrorCode::UnexpectedEnd => "K0005",
+            ErrorCode::ExpectedExpression => "K0006",
+            ErrorCode::UnclosedParenthesis => "K0007",
+            ErrorCode::UnexpectedToken => "K0008",
+            ErrorCode::ExpectedIdentifier => "K0009",
+            ErrorCode::ExpectedAssignment => "K0010",
+            ErrorCode::DivisionByZero => "K0011",
+            ErrorCode::BadUnaryOperand => "K0012",
+            ErrorCode::UnsupportedOperator => "K0013",
+            ErrorCode::UndefinedVariable => "K0014",
+            ErrorCode::DeniedWarning => "K0015",
+            ErrorCode::ReturnOutsideFunction => "K0016",
+            ErrorCode::UnclosedBrace => "K0017",
+            ErrorCode::NotCallable => "K0018",
+            ErrorCode::ArgumentCount => "K0019",
+            ErrorCode::RecursionLimit => "K0020",
+            ErrorCode::InvalidConversion => "K0021",
+            ErrorCode::IntegerOverflow => "K0022",
+            ErrorCode::NegativeShift => "K0023",
+            ErrorCode::UnterminatedString => "K0024",
+            ErrorCode::InvalidEscape => "K0025",
+            ErrorCode::UncaughtError => "K0026",
+            ErrorCode::UnknownMember => "K0027",
+            ErrorCode::ExpectedCatch => "K0028",
+            ErrorCode::NoMatchingArm => "K0029",
+            ErrorCode::ZeroStep => "K0030",
+            ErrorCode::IndexOutOfRange => "K0031",
+            ErrorCode::BreakOutsideLoop => "K0032",
+            ErrorCode::UnterminatedComment => "K0033",
+            ErrorCode::Interrupted => "K0034",
+        }
+    }
+
+    /// Kind of the error value a `catch` receives, it's the same for every error with this code.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidCharacter => "InvalidCharacter",
+            ErrorCode::MalformedNumber => "MalformedNumber",
+            ErrorCode::TypeMismatch => "TypeMismatch",
+            ErrorCode::IncompleteOperator => "IncompleteOperator",
+            ErrorCode::UnexpectedEnd => "UnexpectedEnd",
+            ErrorCode::ExpectedExpression => "ExpectedExpression",
+            ErrorCode::UnclosedParenthesis => "UnclosedParenthesis",
+            ErrorCode::UnexpectedToken => "UnexpectedToken",
+            ErrorCode::ExpectedIdentifier => "ExpectedIdentifier",
+            ErrorCode::ExpectedAssignment => "ExpectedAssignment",
+            ErrorCode::DivisionByZero => "DivisionByZero",
+            ErrorCode::BadUnaryOperand => "BadUnaryOperand",
+            ErrorCode::UnsupportedOperator => "UnsupportedOperator",
+            ErrorCode::UndefinedVariable => "UndefinedVariable",
+            ErrorCode::DeniedWarning => "DeniedWarning",
+            ErrorCode::ReturnOutsideFunction => "ReturnOutsideFunction",
+            ErrorCode::UnclosedBrace => "UnclosedBrace",
+            ErrorCode::NotCallable => "NotCallable",
+            ErrorCode::ArgumentCount => "ArgumentCount",
+            ErrorCode::RecursionLimit => "RecursionLimit",
+            ErrorCode::InvalidConversion => "InvalidConversion",
+            ErrorCode::IntegerOverflow => "IntegerOverflow",
+            ErrorCode::NegativeShift => "NegativeShift",
+            ErrorCode::UnterminatedString => "UnterminatedString",
+            ErrorCode::InvalidEscape => "InvalidEscape",
+            ErrorCode::UncaughtError => "UncaughtError",
+            ErrorCode::UnknownMember => "UnknownMember",
+            ErrorCode::ExpectedCatch => "ExpectedCatch",
+            ErrorCode::NoMatchingArm => "NoMatchingArm",
+            ErrorCode::ZeroStep => "ZeroStep",
+            ErrorCode::IndexOutOfRange => "IndexOutOfRange",
+            ErrorCode::BreakOutsideLoop => "BreakOutsideLoop",
+            ErrorCode::UnterminatedComment => "UnterminatedComment",
+            ErrorCode::Interrupted => "Interrupted",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidCharacter => "invalid character",
+            ErrorCode::MalformedNumber => "malformed number literal",
+            ErrorCode::TypeMismatch => "type mismatch in binary operation",
+            ErrorCode::IncompleteOperator => "incomplete operator",
+            ErrorCode::UnexpectedEnd => "unexpected end of input",
+            ErrorCode::ExpectedExpression => "expected an expression",
+            ErrorCode::UnclosedParenthesis => "unclosed parenthesis",
+            ErrorCode::UnexpectedToken => "unexpected token",
+            ErrorCode::ExpectedIdentifier => "expected an identifier",
+            ErrorCode::ExpectedAssignment => "expected `=`",
+            ErrorCode::DivisionByZero => "division by zero",
+            ErrorCode::BadUnaryOperand => "bad operand for unary operator",
+            ErrorCode::UnsupportedOperator => "unsupported operator",
+            ErrorCode::UndefinedVariable => "undefined variable",
+            ErrorCode::DeniedWarning => "warning denied by --deny-warnings",
+            ErrorCode::ReturnOutsideFunction => "`return` or `?` outside of a function",
+            ErrorCode::UnclosedBrace => "unclosed brace",
+            ErrorCode::NotCallable => "value isn't callable",
+            ErrorCode::ArgumentCount => "wrong number of arguments",
+            ErrorCode::RecursionLimit => "maximum call depth exceeded",
+            ErrorCode::InvalidConversion => "invalid conversion",
+            ErrorCode::IntegerOverflow => "integer too large",
+            ErrorCode::NegativeShift => "negative shift count",
+            ErrorCode::UnterminatedString => "unterminated string",
+            ErrorCode::InvalidEscape => "unknown escape sequence",
+            ErrorCode::UncaughtError => "uncaught error",
+            ErrorCode::UnknownMember => "unknown member",
+            ErrorCode::ExpectedCatch => "`try` without `catch` or `finally`",
+            ErrorCode::NoMatchingArm => "no `match` arm matched the value",
+            ErrorCode::ZeroStep => "range step of zero",
+            ErrorCode::IndexOutOfRange => "index out of range",
+            ErrorCode::BreakOutsideLoop => "`break` or `continue` outside of a loop",
+            ErrorCode::UnterminatedComment => "unterminated block comment",
+            ErrorCode::Interrupted => "evaluation interrupted",
+        }
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidCharacter => r#"The lexer found a character that doesn't start any kumi token.
+
+Example:
+
+    let price = 5 $ 2
+
+`$` isn't an operator in kumi. Remove the character or replace it with one
+of the supported operators."#,
+            ErrorCode::MalformedNumber => r#"A number literal couldn't be read.
+
+Example:
+
+    let mask = 0b102
+
+`2` isn't a binary digit. Number literals are written as
+
+    1_000_000            ints, `_` may separate digits
+    0xFF 0o755 0b1010    hex, octal and binary ints
+    0.5 1. 1.5e-3        floats, the dot needs a digit before it
+    19.99d 1/3r          decimals and rationals
+
+The error points at the first character that doesn't fit the literal."#,
+            ErrorCode::TypeMismatch => r#"A binary operator was applied to operands of types it doesn't support
+together.
+
+Example:
+
+    let a = 1
+    a + (1 == 1)
+
+`+` is defined for two numbers, but the right operand is a bool. Numbers
+of different types can be mixed freely, the operand lower in the order
+int, decimal, rational, float is promoted to the type of the other one.
+
+`==` and `!=` accept any two values, values of different types are simply
+not equal. Ordering with `<`, `<=`, `>` and `>=` works for numbers, strings
+and bools, each only among its own kind."#,
+            ErrorCode::IncompleteOperator => r#"An operator made of two characters was cut short.
+
+Older versions reported a single `|` or `&` with this code. Both are
+bitwise operators now, so no operator can be incomplete and the code is
+kept only so that it isn't reused."#,
+            ErrorCode::UnexpectedEnd => r#"The input ended while the parser was still expecting more tokens.
+
+Example:
+
+    let a = 1 +
+
+The right operand of `+` is missing. Finish the expression."#,
+            ErrorCode::ExpectedExpression => r#"The parser expected a value (a number, a variable or a parenthesized
+expression) but found something else.
+
+Example:
+
+    let a = * 2
+
+`*` can't start an expression. Put a value in front of the operator."#,
+            ErrorCode::UnclosedParenthesis => r#"A `(` was opened but never closed.
+
+Example:
+
+    (1 + 2 * 3
+
+Add the missing `)` where the grouped expression ends."#,
+            ErrorCode::UnexpectedToken => r#"The parser read a complete expression but more tokens followed it.
+
+Example:
+
+    1 2
+
+Two values must be joined by an operator, for example `1 + 2`."#,
+            ErrorCode::ExpectedIdentifier => r#"A name was expected, usually right after the `let` keyword.
+
+Example:
+
+    let 1 = 2
+
+Variable names start with a letter or `_` and may contain digits."#,
+            ErrorCode::ExpectedAssignment => r#"A `let` declaration is missing the `=` between the name and the value.
+
+Example:
+
+    let a 5
+
+Write the declaration as `let a = 5`."#,
+            ErrorCode::DivisionByZero => r#"The right operand of `/` or `%` evaluated to zero, or zero was raised
+to a negative power.
+
+Example:
+
+    let zero = 0
+    10 / zero
+
+Check the divisor before dividing."#,
+            ErrorCode::BadUnaryOperand => r#"A unary operator was applied to a value it doesn't support.
+
+Example:
+
+    !5
+
+`!` negates bools only, unary `-` negates numbers only and unary `~`
+flips the bits of ints only."#,
+            ErrorCode::UnsupportedOperator => r#"The operator exists in the syntax but has no evaluation rule at all.
+
+Every operator kumi can currently parse is implemented, so this error
+points at an interpreter bug. Please report the program that caused it."#,
+            ErrorCode::UndefinedVariable => r#"A variable was read before it was declared.
+
+Example:
+
+    let a = b + 1
+
+`b` has no value yet. Declare it with `let b = ...` before using it, and
+check the name for typos."#,
+            ErrorCode::DeniedWarning => r#"The program produced lint warnings and kumi was started with
+`--deny-warnings`, so the warnings were promoted to errors and nothing was
+evaluated.
+
+Example:
+
+    let unused = 1
+    2 + 2
+
+run as `kumi --deny-warnings script.kumi` fails because `unused` is never
+read. Fix the reported warnings, each one can be looked up with
+`kumi --explain W....`."#,
+            ErrorCode::ReturnOutsideFunction => r#"`return` can only be used inside a function body, and so can `?` since it
+returns an `err` early.
+
+Example:
+
+    let a = 1
+    return a
+
+At the top level of a program there is no function to return from. The
+value of the last statement is the result of the program. Use `match` to
+handle an `ok` or `err` value at the top level."#,
+            ErrorCode::UnclosedBrace => r#"A `{` was opened but never closed.
+
+Example:
+
+    fn double(x) {
+        x * 2
+
+Add the missing `}` where the block ends."#,
+            ErrorCode::NotCallable => r#"A value that isn't a function was called.
+
+Example:
+
+    let a = 5
+    a(1)
+
+Only functions declared with `fn` can be called."#,
+            ErrorCode::ArgumentCount => r#"A function was called with a different number of arguments than it
+declares parameters.
+
+Example:
+
+    fn add(a, b) {
+        a + b
+    }
+    add(1)
+
+`add` takes 2 arguments, pass exactly that many."#,
+            ErrorCode::RecursionLimit => r#"Function calls were nested deeper than kumi allows, usually because a
+recursive function never reaches its base case.
+
+Example:
+
+    fn forever(n) {
+        forever(n + 1)
+    }
+    forever(0)
+
+Make sure every recursive call gets closer to a case that returns without
+recursing."#,
+            ErrorCode::InvalidConversion => r#"A value couldn't be converted by `int()`, `float()`, `rational()` or `decimal()`.
+
+Example:
+
+    int(10.0 ^ 400.0)
+
+An infinite or NaN float has no integer value, and values such as
+functions can't be converted to numbers at all."#,
+            ErrorCode::IntegerOverflow => r#"The result of an integer operation would be too large to hold.
+
+Example:
+
+    let big = 2 ^ 1000000
+    big * big
+
+Ints grow as needed, but results wider than 1048576 bits (about 315000
+decimal digits) are refused instead of exhausting memory. Convert an
+operand with `float()` if an approximate result is good enough."#,
+            ErrorCode::NegativeShift => r#"An int was shifted with `<<` or `>>` by a negative number of bits.
+
+Example:
+
+    let flags = 1 << -1
+
+Shift counts must be zero or positive. Shift the other way instead, for
+example `1 >> 1` rather than `1 << -1`."#,
+            ErrorCode::UnterminatedString => r#"A string literal was opened with `"` but the input ended before the
+closing `"`.
+
+Example:
+
+    let greeting = "hello
+
+Add the missing `"`. A `"` inside a string must be escaped as `\"`."#,
+            ErrorCode::InvalidEscape => r#"A `\` in a string literal was followed by a character that doesn't form
+an escape sequence.
+
+Example:
+
+    let path = "C:\kumi"
+
+The supported escapes are `\n`, `\t`, `\r`, `\0`, `\\` and `\"`. Write `\\`
+for a literal backslash."#,
+            ErrorCode::UncaughtError => r#"A value thrown with `throw` wasn't caught by any `try`.
+
+Example:
+
+    fn parse(text) {
+        throw "can't parse " + text
+    }
+    parse("x")
+
+Wrap the call in `try { ... } catch e { ... }` to handle the error, a
+`catch` can also be limited to some kinds of errors:
+
+    try {
+        parse("x")
+    } catch Error | DivisionByZero e {
+        e.message
+    }
+
+Errors made with `error(kind, message)` are caught by their own kind,
+errors kumi reports itself by the kind `kumi --explain` prints for their
+code. Any other value is thrown as an `Error`, a string becomes its message
+and other values are shown the way the REPL prints them, `e.value` gives the
+value back."#,
+            ErrorCode::UnknownMember => r#"A member was read with `.` or `?.` from a value that doesn't have it.
+
+Example:
+
+    let answer = 42
+    answer.message
+
+Error values have the members `message`, `kind`, `code`, `span`, `line`,
+`column` and `value`, functions have `name` and `doc`. Ranges, lists and
+strings have the methods `len()`, `rev()` and `contains(value)`. `?.` returns `none` instead of failing when the value
+itself is `none`, but a missing member is still an error."#,
+            ErrorCode::ExpectedCatch => r#"A `try` block must be followed by at least one `catch` or a `finally`.
+
+Example:
+
+    try {
+        risky()
+    }
+
+Add `catch e { ... }` to handle errors, or `finally { ... }` to run code
+whether or not an error happened."#,
+            ErrorCode::NoMatchingArm => r#"A `match` was evaluated with a value that none of its arms accepts.
+
+Example:
+
+    match ok(1) {
+        err(e) => 0
+    }
+
+Add an arm for the missing case, or end the `match` with `_ => ...` to
+handle every other value."#,
+            ErrorCode::ZeroStep => r#"A range was made with `step 0`, it would never get from its start to its
+end.
+
+Example:
+
+    for i in 0..10 step 0 {
+        i
+    }
+
+Use a positive step to count up or a negative one to count down, for
+example `10..0 step -1`."#,
+            ErrorCode::IndexOutOfRange => r#"A list or a string was indexed at a position it doesn't have.
+
+Example:
+
+    let xs = [1, 2, 3]
+    xs[3]
+
+Positions start at 0, so the last one is `len() - 1`. A negative index
+counts from the end, `xs[-1]` is the last element. Every position of a
+slice like `xs[1..5]` must exist as well."#,
+            ErrorCode::BreakOutsideLoop => r#"`break` and `continue` can only be used inside the body of a `for` loop.
+
+Example:
+
+    fn first(xs) {
+        break
+    }
+
+A function body is not part of the loop it's called from, use `return` to
+leave a function early."#,
+            ErrorCode::UnterminatedComment => r#"A `/*` block comment isn't closed by a matching `*/` before the end of the
+input.
+
+Example:
+
+    /* the answer
+    42
+
+Block comments nest, so every `/*` inside a comment needs its own `*/`:
+
+    /* outer /* inner */ still a comment */
+
+The error points at the `/*` that is still open."#,
+            ErrorCode::Interrupted => r#"Ctrl-C was pressed while the REPL was evaluating the input.
+
+Example:
+
+    kumi> for i in 0..1000000000 { i }
+    ^C
+
+The evaluation stops at the next loop iteration or function call and the
+session goes on with the variables it had. `try`/`catch` can't catch this
+error, a `finally` block still runs."#,
+        }
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            ErrorCode::UndefinedVariable => io::ErrorKind::NotFound,
+            _ => io::ErrorKind::InvalidInput
+        }
+    }
+
+    /// Creates an [`io::Error`] tagged with this code.
+    pub fn error<M: ToString>(&self, message: M) -> io::Error {
+        io::Error::new(self.kind(), CodedError { code: *self, message: message.to_string() })
+    }
+}
+
+impl ToString for ErrorCode {
+    fn to_string(&self) -> String {
+        format!("{}: {}", self.code(), self.title())
+    }
+}
+
+impl Debug for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+pub struct CodedError {
+    code: ErrorCode,
+    message: String
+}
+
+impl CodedError {
+    /// The coded error inside `err`, `None` when `err` wasn't created by [`ErrorCode::error`].
+    pub fn of(err: &io::Error) -> Option<&Self> {
+        err.get_ref().and_then(|inner| inner.downcast_ref::<Self>())
+    }
+
+    pub fn get_code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code.code(), self.message)
+    }
+}
+
+impl Debug for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for CodedError {}
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::io;
+
+use crate::context::{Context, Traceback};
+use crate::error_code::{CodedError, ErrorCode};
+use crate::types::Type;
+
+/// Kind of errors thrown from a value that isn't an error, like `throw "not found"`.
+const DEFAULT_KIND: &str = "Error";
+
+/// Place in a source where an error happened, line and column start at 1.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Span {
+    source: String,
+    line: usize,
+    column: usize
+}
+
+impl Span {
+    pub fn new(source: &str, line: usize, column: usize) -> Self {
+        Self { source: source.to_string(), line, column }
+    }
+}
+
+impl ToString for Span {
+    fn to_string(&self) -> String {
+        format!("{}:{}:{}", self.source, self.line, self.column)
+    }
+}
+
+/// An error as a kumi value, either thrown by the program or caught from a failing operation.
+#[derive(Clone)]
+pub struct ErrorValue {
+    kind: String,
+    message: String,
+    code: Option<ErrorCode>,
+    span: Option<Span>,
+    /// What was thrown when it wasn't an error itself, like `5` for `throw 5`.
+    value: Option<Type>
+}
+
+impl ErrorValue {
+    pub fn new(kind: &str, message: &str) -> Self {
+        Self { kind: kind.to_string(), message: message.to_string(), code: None, span: None, value: None }
+    }
+
+    /// The error `throw value` raises for a value that isn't an error, a string is its message as it is.
+    pub fn wrap(value: Type) -> Self {
+        let message = match &value {
+            Type::Str(message) => message.clone(),
+            value => value.to_string(),
+        };
+
+        Self { value: Some(value), ..Self::new(DEFAULT_KIND, &message) }
+    }
+
+    /// Turns a runtime error into the value a `catch` receives.
+    ///
+    /// Thrown values come back unchanged. Other errors get the kind of their [`ErrorCode`] and the
+    /// position of the innermost frame they passed, `context` is that frame unless they left a function.
+    pub fn from_error(err: &io::Error, context: &Context) -> Self {
+        let (err, span) = match err.get_ref().and_then(|inner| inner.downcast_ref::<Traceback>()) {
+            Some(traceback) => (traceback.get_error(), traceback.get_span().clone()),
+            None => (err, context.span()),
+        };
+
+        if let Some(thrown) = err.get_ref().and_then(|inner| inner.downcast_ref::<Thrown>()) {
+            return thrown.0.clone();
+        }
+
+        match CodedError::of(err) {
+            Some(coded) => Self {
+                kind: coded.get_code().name().to_string(),
+                message: coded.get_message().to_string(),
+                code: Some(coded.get_code()),
+                span: Some(span),
+                value: None
+            },
+            None => Self { span: Some(span), ..Self::new(DEFAULT_KIND, &err.to_string()) },
+        }
+    }
+
+    /// The error to propagate for `throw`, a value thrown for the first time is marked with `span`.
+    pub fn throw(mut self, span: Span) -> io::Error {
+        self.span.get_or_insert(span);
+
+        io::Error::new(self.code.map_or(io::ErrorKind::Other, |code| code.kind()), Thrown(self))
+    }
+
+    pub fn get_kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Members readable with `.`, `None` for unknown names.
+    pub fn get_member(&self, name: &str) -> Option<Type> {
+        let span = self.span.as_ref();
+
+        match name {
+            "message" => Some(Type::Str(self.message.clone())),
+            "kind" => Some(Type::Str(self.kind.clone())),
+            "code" => Some(self.code.map_or(Type::None, |code| Type::Str(code.code().to_string()))),
+            "span" => Some(span.map_or(Type::None, |span| Type::Str(span.to_string()))),
+            "line" => Some(span.map_or(Type::None, |span| Type::Int((span.line as i128).into()))),
+            "column" => Some(span.map_or(Type::None, |span| Type::Int((span.column as i128).into()))),
+            "value" => Some(self.value.clone().unwrap_or(Type::None)),
+            _ => None
+        }
+    }
+}
+
+impl PartialEq for ErrorValue {
+    fn eq(&self, other: &Self) -> bool {
+        let values_equal = match (&self.value, &other.value) {
+            (Some(a), Some(b)) => a.equals(b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+
+        self.kind == other.kind && self.message == other.message && self.code == other.code && self.span == other.span && values_equal
+    }
+}
+
+impl ToString for ErrorValue {
+    fn to_string(&self) -> String {
+        format!("{}: {}", self.kind, self.message)
+    }
+}
+
+/// An [`ErrorValue`] travelling up the call stack after `throw`.
+struct Thrown(ErrorValue);
+
+impl Display for Thrown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.code {
+            Some(code) => write!(f, "[{}] {}", code.code(), self.0.message),
+            None => write!(f, "[{}] {}", ErrorCode::UncaughtError.code(), self.0.to_string()),
+        }
+    }
+}
+
+impl Debug for Thrown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for Thrown {}
+
+// SAFETY: the thrown value may hold `Rc`s, but a `Thrown` never leaves the interpreter thread, the only
+// one evaluating kumi code, since it's caught or reported there and the thread returns nothing
+unsafe impl Send for Thrown {}
+unsafe impl Sync for Thrown {}
\ No newline at end of file
@@ -0,0 +1,395 @@
+use std::io;
+
+use crate::cst::{CstToken, SyntaxNode, TriviaKind};
+use crate::keyword::Keyword;
+use crate::op::Op;
+use crate::parser::{conditional_questions, Parser};
+use crate::token::{TT, Token};
+use crate::types::Type;
+
+const INDENT: &str = "    ";
+
+/// Prints kumi source in the canonical style used by `kumi fmt`.
+///
+/// Only whitespace and `;` change: binary operators get a space on both sides, blocks are indented by
+/// four spaces, statements go on lines of their own, runs of blank lines shrink to one and comments stay
+/// where they were written. A block is printed over several lines when it was written that way or holds
+/// more than one statement, and then so are the other blocks of its `if`/`else` or `try`/`catch` chain.
+pub struct Formatter<'t> {
+    tokens: Vec<&'t CstToken>,
+    /// For each `{`, whether its block is printed over several lines, with every statement on its own line.
+    multiline: Vec<bool>,
+    /// Which tokens are the `?` of a `cond ? a : b`, as the [`Parser`] reads them.
+    conditionals: Vec<bool>,
+    /// Whether each block the printed text is inside of is multiline.
+    blocks: Vec<bool>,
+    out: String,
+    indent: usize,
+    prev: Option<&'t TT>,
+    prev_unary: bool,
+    prev_postfix: bool,
+    after_comment: bool,
+    /// Set after a new line or `;` between statements, until the next token is printed.
+    statement_break: bool,
+    /// Set while nothing has been printed on the lines of a block after its `{` line.
+    block_start: bool
+}
+
+impl<'t> Formatter<'t> {
+    /// Formats `text`, which has to parse, the result formats to itself again.
+    pub fn format(text: &str) -> io::Result<String> {
+        let tree = SyntaxNode::parse(text)?;
+        let tokens = tree.tokens();
+
+        let plain: Vec<Token> = tokens.iter().map(|token| token.get_token().clone()).collect();
+        Parser::new(&plain).parse().map_err(|err| err.format(text))?;
+
+        let mut formatter = Formatter {
+            multiline: multiline_blocks(&tokens),
+            conditionals: conditional_questions(&tokens.iter().map(|token| token.get_tt()).collect::<Vec<_>>()),
+            tokens,
+            blocks: Vec::new(),
+            out: String::new(),
+            indent: 0,
+            prev: None,
+            prev_unary: false,
+            prev_postfix: false,
+            after_comment: false,
+            statement_break: false,
+            block_start: false
+        };
+
+        for index in 0..formatter.tokens.len() {
+            formatter.token(index)
+        }
+
+        Ok(formatter.out)
+    }
+
+    fn token(&mut self, index: usize) {
+        let token = self.tokens[index];
+        for trivia in token.get_leading() {
+            match trivia.get_kind() {
+                TriviaKind::Comment => self.comment(trivia.get_text()),
+                TriviaKind::NewLine => self.new_line(),
+                TriviaKind::Whitespace => {},
+            }
+        }
+
+        match token.get_tt() {
+            // `} else`, `} catch` and `} finally` stay on one line
+            TT::NL if !self.after_comment && matches!(self.prev, Some(TT::RBR)) && continues_chain(&self.tokens[index..]) => return,
+            TT::NL => {
+                self.statement_break = true;
+                return self.new_line()
+            },
+            // `;` between statements becomes a new line, one ending the last statement goes away
+            TT::SEMI => {
+                if !self.tokens.get(index + 1).is_some_and(|next| matches!(next.get_tt(), TT::NL | TT::SEMI | TT::EOF | TT::RBR)) {
+                    self.new_line()
+                }
+                self.statement_break = true;
+                return
+            },
+            TT::EOF => return self.finish(),
+            TT::RPR | TT::RSQ | TT::RBR => self.indent = self.indent.saturating_sub(1),
+            _ => {},
+        }
+
+        if matches!(token.get_tt(), TT::RBR) && self.blocks.pop().unwrap_or(false) {
+            if !self.at_line_start() {
+                self.new_line()
+            }
+            // no blank line in front of a closing brace
+            while self.out.ends_with("\n\n") {
+                self.out.pop();
+            }
+        }
+
+        let mut starts_statement = false;
+        if self.at_line_start() {
+            // a `cond ? a : b`, a binary operator or a `let` split over lines continues its statement one
+            // level deeper
+            let continued = matches!(token.get_tt(), TT::COLON)
+                || matches!(self.prev, Some(TT::COLON | TT::EQ))
+                || matches!(self.prev, Some(TT::QUESTION)) && !self.prev_postfix
+                || matches!(self.prev, Some(TT::Op(_))) && !self.prev_unary;
+            self.out += &INDENT.repeat(self.indent + continued as usize);
+            self.block_start = false;
+            starts_statement = self.statement_break && !continued;
+        } else if self.needs_space(index) {
+            self.out.push(' ');
+        }
+
+        self.out += token.get_text().trim_end();
+        self.prev_unary = matches!(token.get_tt(), TT::Op(Op::Sub | Op::Not | Op::Xor)) && (starts_statement || !self.prev_ends_expression());
+        self.prev_postfix = self.is_postfix(index);
+        self.prev = Some(token.get_tt());
+        self.after_comment = false;
+        self.statement_break = false;
+
+        if matches!(token.get_tt(), TT::LPR | TT::LSQ | TT::LBR) {
+            self.indent += 1;
+        }
+        if matches!(token.get_tt(), TT::LBR) {
+            self.blocks.push(self.multiline[index]);
+            self.block_start = self.multiline[index];
+            if self.multiline[index] && !self.tokens.get(index + 1).is_some_and(|next| matches!(next.get_tt(), TT::NL)) {
+                self.new_line()
+            }
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        if self.at_line_start() {
+            self.out += &INDENT.repeat(self.indent);
+            self.block_start = false;
+        } else if !self.out.ends_with(['(', '[']) {
+            self.out.push(' ');
+        }
+
+        self.out += text.trim_end();
+        self.after_comment = true;
+    }
+
+    fn new_line(&mut self) {
+        // the start of the file or of a block and a blank line already there take no more new lines
+        let blank = self.out.is_empty() || self.out.ends_with("\n\n") || (self.block_start && self.at_line_start());
+        if !blank {
+            while self.out.ends_with(' ') {
+                self.out.pop();
+            }
+            self.out.push('\n');
+        }
+
+        self.after_comment = false;
+    }
+
+    fn finish(&mut self) {
+        let len = self.out.trim_end().len();
+        self.out.truncate(len);
+
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn prev_ends_expression(&self) -> bool {
+        match self.prev {
+            Some(TT::Type(_) | TT::Indenifier(_) | TT::RPR | TT::RSQ | TT::RBR) => true,
+            Some(TT::QUESTION) => self.prev_postfix,
+            _ => false
+        }
+    }
+
+    fn needs_space(&self, index: usize) -> bool {
+        let current = self.tokens[index].get_tt();
+        if self.after_comment {
+            return !matches!(current, TT::RPR | TT::RSQ | TT::COMMA | TT::SEMI);
+        }
+
+        let prev = match self.prev {
+            Some(prev) => prev,
+            None => return false,
+        };
+
+        match (prev, current) {
+            // `x? .y` and `x? ?? y` would lex as `?.` and `??` without the space
+            (TT::QUESTION, TT::DOT | TT::QUESTION | TT::Op(Op::Coalesce)) => true,
+            (TT::Type(Type::Int(_)), TT::DOT) => true,
+            _ if self.prev_unary => false,
+            (TT::LPR | TT::LSQ | TT::DOT | TT::QDOT | TT::DOTDOT | TT::DOTDOTEQ, _) => false,
+            (_, TT::RPR | TT::RSQ | TT::COMMA | TT::SEMI | TT::DOT | TT::QDOT | TT::DOTDOT | TT::DOTDOTEQ) => false,
+            (TT::LBR, TT::RBR) => false,
+            (_, TT::QUESTION) => !self.is_postfix(index),
+            // calls and indexing
+            (TT::Indenifier(_) | TT::RPR | TT::RSQ | TT::QUESTION, TT::LPR | TT::LSQ) => !self.prev_ends_expression(),
+            (TT::Type(_), TT::LSQ) => false,
+            _ => true
+        }
+    }
+
+    /// Whether the token at `index` is a `?` unwrapping a result rather than starting a `cond ? a : b`.
+    fn is_postfix(&self, index: usize) -> bool {
+        matches!(self.tokens[index].get_tt(), TT::QUESTION) && !self.conditionals[index]
+    }
+}
+
+/// Whether the first token after the new lines of `tokens` goes on with an `if`/`else` or `try`/`catch`
+/// chain, a comment in between keeps the line break.
+fn continues_chain(tokens: &[&CstToken]) -> bool {
+    for token in tokens {
+        if token.get_leading().iter().any(|trivia| matches!(trivia.get_kind(), TriviaKind::Comment)) {
+            return false;
+        }
+        match token.get_tt() {
+            TT::NL => {},
+            tt => return matches!(tt, TT::Keyword(Keyword::Else | Keyword::Catch | Keyword::Finally)),
+        }
+    }
+
+    false
+}
+
+/// Marks the `{` of every block with a new line, a `;` or a line comment inside it, nested blocks
+/// included, and then the other blocks of the `if`/`else` or `try`/`catch` chains these are part of.
+fn multiline_blocks(tokens: &[&CstToken]) -> Vec<bool> {
+    let mut multiline = vec![false; tokens.len()];
+    let mut open: Vec<usize> = Vec::new();
+    // pairs of a block's `{` and the `{` of the block chained after it with `else`, `catch` or `finally`
+    let mut chained: Vec<(usize, usize)> = Vec::new();
+    let mut closed = None;
+    let mut chain = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let breaks = token.get_leading().iter().any(|trivia| match trivia.get_kind() {
+            TriviaKind::NewLine => true,
+            TriviaKind::Comment => !trivia.get_text().starts_with("/*"),
+            TriviaKind::Whitespace => false,
+        });
+        if breaks || matches!(token.get_tt(), TT::NL | TT::SEMI | TT::Doc(_)) {
+            open.iter().for_each(|&open| multiline[open] = true);
+        }
+
+        match token.get_tt() {
+            TT::LBR => {
+                if let Some(previous) = chain.take() {
+                    chained.push((previous, index))
+                }
+                open.push(index)
+            },
+            TT::RBR => closed = open.pop(),
+            TT::Keyword(Keyword::Else | Keyword::Catch | Keyword::Finally) => chain = closed.take(),
+            TT::NL => {},
+            _ => closed = None,
+        }
+    }
+
+    // the pairs of a chain come in order, so one pass each way reaches all of its blocks
+    for &(previous, next) in &chained {
+        multiline[next] |= multiline[previous];
+    }
+    for &(previous, next) in chained.iter().rev() {
+        multiline[previous] |= multiline[next];
+    }
+
+    multiline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Formatter;
+
+    /// Formats `text` twice and checks the second pass keeps the first one's output.
+    fn format_twice(text: &str) -> String {
+        let once = Formatter::format(text).unwrap_or_else(|err| panic!("{} failed: {}", text, err));
+        let twice = Formatter::format(&once).unwrap_or_else(|err| panic!("{} failed: {}", once, err));
+        assert_eq!(twice, once, "formatting again changed the output of {:?}", text);
+
+        once
+    }
+
+    #[test]
+    fn formats_to_a_fixed_point() {
+        let formatted = format_twice("\
+# leading comment
+fn  f(x){   # after the brace
+  let r=ok(x)
+    /* block
+       comment */
+  let y=r? - 1
+  if y>0 {
+ for i in 0..y{
+   if i%2==0{ print(i) }   else {  print(-i)}
+ }
+  }
+  let t = y > 0 ?
+      y * 2 :
+   -y
+  let u = y > 0 ? ok(1)? - 1
+  : 5
+  return t+u # trailing
+}
+
+
+f(3)");
+        assert_eq!(formatted, "\
+# leading comment
+fn f(x) { # after the brace
+    let r = ok(x)
+    /* block
+       comment */
+    let y = r? - 1
+    if y > 0 {
+        for i in 0..y {
+            if i % 2 == 0 { print(i) } else { print(-i) }
+        }
+    }
+    let t = y > 0 ?
+        y * 2 :
+        -y
+    let u = y > 0 ? ok(1)? - 1
+        : 5
+    return t + u # trailing
+}
+
+f(3)
+");
+    }
+
+    #[test]
+    fn formatted_text_formats_to_itself() {
+        for text in [
+            "fn f(r) { r? - 1 }",
+            "fn f(r) { let x = r?-1\nx>0?-1:1 }",
+            "let x = 1\nlet y = x > 0 ? 1\n  : 2",
+            "# only a comment",
+            "fn f() {\n\n\n    # first\n    1 # one\n\n\n    # last\n}",
+            "fn f() { if true { if true { 1 } } }",
+            "fn f(x) {\nif x { if !x { 1 } else { [1,2][0] } } else { 2 }\n}",
+            "let t = true ? 1 :\n2 # why",
+            "let x =\n5\nlet y = 1 +\n2",
+        ] {
+            format_twice(text);
+        }
+    }
+
+    #[test]
+    fn equivalent_layouts_format_the_same() {
+        let groups: [(&[&str], &str); 4] = [
+            (
+                &["fn f(x) { if x>0 {\nreturn x } else { -x } }", "fn f(x) { if x>0 { return x } else {\n-x } }"],
+                "fn f(x) {\n    if x > 0 {\n        return x\n    } else {\n        -x\n    }\n}\n",
+            ),
+            (
+                &["let a=1;let b=2", "let a = 1\nlet b = 2;", "let a = 1;\nlet b = 2;;"],
+                "let a = 1\nlet b = 2\n",
+            ),
+            (
+                &["fn f(r) { let y = r?; -y }", "fn f(r) {\nlet y = r?\n-y }"],
+                "fn f(r) {\n    let y = r?\n    -y\n}\n",
+            ),
+            (
+                &["try { 1 } catch e {\n2 } finally { 3 }", "try {\n1 }\ncatch e { 2 }\nfinally { 3 }"],
+                "try {\n    1\n} catch e {\n    2\n} finally {\n    3\n}\n",
+            ),
+        ];
+
+        for (inputs, expected) in groups {
+            for input in inputs {
+                assert_eq!(format_twice(input), expected, "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn chains_written_on_one_line_stay_inline() {
+        assert_eq!(format_twice("fn f(x) { if x>0 { return x } else { -x } }"), "fn f(x) { if x > 0 { return x } else { -x } }\n");
+        assert_eq!(format_twice("fn f(x) {\nif x > 0 { return x }\nelse { -x }\n}"), "fn f(x) {\n    if x > 0 { return x } else { -x }\n}\n");
+        assert_eq!(format_twice("if true { 1 } # why\nelse { 2 }"), "if true { 1 } # why\nelse { 2 }\n");
+    }
+}
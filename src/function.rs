@@ -0,0 +1,65 @@
+use std::io;
+use std::rc::Rc;
+
+use crate::context::Context;
+use crate::error_code::ErrorCode;
+use crate::node::{Interrupt, Node};
+use crate::signal;
+use crate::source::Source;
+use crate::types::Type;
+use crate::variable::Variable;
+
+/// Calls nested deeper than this fail instead of overflowing the interpreter's own stack.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+pub struct Function {
+    name: String,
+    params: Vec<String>,
+    body: Rc<Node>,
+    doc: Option<String>,
+    source: Rc<Source>,
+    /// Id of the scope the function was declared in, its body sees the names of that scope.
+    scope: usize
+}
+
+impl Function {
+    pub fn new(name: &str, params: Vec<String>, body: Rc<Node>, doc: Option<String>, source: Rc<Source>, scope: usize) -> Self {
+        Self { name: name.to_string(), params, body, doc, source, scope }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Text of the `///` comments written right above the declaration.
+    pub fn get_doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
+    pub fn call(&self, args: Vec<Type>, context: &Context) -> io::Result<Type> {
+        signal::check_interrupt()?;
+
+        if context.get_depth() >= MAX_CALL_DEPTH {
+            return Err(ErrorCode::RecursionLimit.error(
+                format!("can\'t call {}, maximum call depth of {} exceeded", self.name, MAX_CALL_DEPTH)
+            ));
+        }
+
+        if args.len() != self.params.len() {
+            return Err(ErrorCode::ArgumentCount.error(
+                format!("{} takes {} argument(s) but {} were given", self.name, self.params.len(), args.len())
+            ));
+        }
+
+        let mut child = Context::call(&self.name, context, context.find_scope(self.scope), self.source.clone());
+        for (param, arg) in self.params.iter().zip(args) {
+            child.get_symbol_table_mut().declare(Variable::new(param, arg));
+        }
+
+        match self.body.eval(&mut child) {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            Err(Interrupt::Break | Interrupt::Continue) => Ok(Type::None),
+            Err(Interrupt::Error(err)) => Err(child.trace(err)),
+        }
+    }
+}
\ No newline at end of file
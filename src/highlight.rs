@@ -0,0 +1,181 @@
+use crate::analysis::{Analysis, SymbolKind};
+use crate::cst::{attach_trivia, CstToken, TriviaKind};
+use crate::lexer::Lexer;
+use crate::op::Op;
+use crate::token::TT;
+use crate::types::Type;
+
+/// What a piece of source text is, for syntax highlighting.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenClass {
+    Keyword,
+    Variable,
+    Function,
+    Number,
+    String,
+    Operator,
+    Comment,
+    /// Text the lexer or parser rejects.
+    Error
+}
+
+impl TokenClass {
+    pub fn classes() -> Vec<Self> {
+        vec![
+            TokenClass::Keyword,
+            TokenClass::Variable,
+            TokenClass::Function,
+            TokenClass::Number,
+            TokenClass::String,
+            TokenClass::Operator,
+            TokenClass::Comment,
+            TokenClass::Error
+        ]
+    }
+
+    /// The LSP semantic token type, all but `error` are predefined ones.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TokenClass::Keyword => "keyword",
+            TokenClass::Variable => "variable",
+            TokenClass::Function => "function",
+            TokenClass::Number => "number",
+            TokenClass::String => "string",
+            TokenClass::Operator => "operator",
+            TokenClass::Comment => "comment",
+            TokenClass::Error => "error",
+        }
+    }
+
+    /// ANSI escape the REPL colours the class with, `None` keeps the terminal's colour.
+    fn color(&self) -> Option<&'static str> {
+        match self {
+            TokenClass::Keyword => Some("\x1b[35m"),
+            TokenClass::Variable => None,
+            TokenClass::Function => Some("\x1b[34m"),
+            TokenClass::Number => Some("\x1b[33m"),
+            TokenClass::String => Some("\x1b[32m"),
+            TokenClass::Operator => Some("\x1b[36m"),
+            TokenClass::Comment => Some("\x1b[90m"),
+            TokenClass::Error => Some("\x1b[31;4m"),
+        }
+    }
+}
+
+/// Chars `start..end` of the text are of `class`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClassifiedSpan {
+    start: usize,
+    end: usize,
+    class: TokenClass
+}
+
+impl ClassifiedSpan {
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    pub fn get_class(&self) -> TokenClass {
+        self.class
+    }
+}
+
+/// Classifies `text` for highlighting, the spans are in order and don't overlap.
+///
+/// Names are told apart by resolving them, one that can't be resolved is a function when it's called.
+/// Text after a lexer error is a single [`TokenClass::Error`] span, a token the parser rejects is one too.
+pub fn classify(text: &str) -> Vec<ClassifiedSpan> {
+    let chars: Vec<char> = text.chars().collect();
+    let (tokens, lexed) = lex_prefix(&chars);
+    let analysis = Analysis::new(text);
+
+    let mut spans = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let (start, end) = (*token.get_token().get_start(), *token.get_token().get_end());
+
+        let mut trivia_start = start - token.get_leading().iter().map(|trivia| trivia.get_text().chars().count()).sum::<usize>();
+        for trivia in token.get_leading() {
+            let len = trivia.get_text().chars().count();
+            if trivia.get_kind() == TriviaKind::Comment {
+                spans.push(ClassifiedSpan { start: trivia_start, end: trivia_start + len, class: TokenClass::Comment });
+            }
+            trivia_start += len
+        }
+
+        let class = match token.get_tt() {
+            TT::Keyword(_) | TT::Op(Op::In) | TT::Type(Type::Bool(_) | Type::None) => TokenClass::Keyword,
+            TT::Type(Type::Str(_)) => TokenClass::String,
+            TT::Type(_) => TokenClass::Number,
+            TT::Doc(_) => TokenClass::Comment,
+            TT::Op(_) | TT::EQ | TT::ARROW | TT::QUESTION | TT::QDOT | TT::DOT | TT::DOTDOT | TT::DOTDOTEQ | TT::COLON => TokenClass::Operator,
+            TT::Indenifier(_) => match analysis.symbol_at(start).map(|(_, symbol)| symbol.get_kind()) {
+                Some(SymbolKind::Function | SymbolKind::Builtin) => TokenClass::Function,
+                Some(_) => TokenClass::Variable,
+                None if tokens.get(index + 1).is_some_and(|next| matches!(next.get_tt(), TT::LPR)) => TokenClass::Function,
+                None => TokenClass::Variable,
+            },
+            TT::LPR | TT::RPR | TT::LBR | TT::RBR | TT::LSQ | TT::RSQ | TT::COMMA | TT::SEMI | TT::NL | TT::EOF => continue,
+        };
+        spans.push(ClassifiedSpan { start, end, class });
+    }
+
+    if lexed < chars.len() {
+        spans.push(ClassifiedSpan { start: lexed, end: chars.len(), class: TokenClass::Error });
+    } else if let Some(error) = analysis.get_diagnostics().iter().find(|diagnostic| !diagnostic.is_warning()) {
+        let (start, end) = (error.get_start(), error.get_end());
+        if start < end {
+            spans.retain(|span| span.end <= start || end <= span.start);
+            spans.push(ClassifiedSpan { start, end, class: TokenClass::Error });
+            spans.sort_by_key(|span| span.start);
+        }
+    }
+
+    spans
+}
+
+/// Tokens of the longest start of `chars` the lexer accepts, with the number of chars it covers.
+fn lex_prefix(chars: &[char]) -> (Vec<CstToken>, usize) {
+    let mut len = chars.len();
+
+    loop {
+        let text: String = chars[..len].iter().collect();
+        match Lexer::new(&text).try_tokenize() {
+            Ok(tokens) => return (attach_trivia(&chars[..len], tokens), len),
+            Err((pos, _)) => len = pos.min(len.saturating_sub(1)),
+        }
+    }
+}
+
+/// `text` with ANSI colours for a terminal.
+pub fn colorize(text: &str) -> String {
+    colorize_after("", text)
+}
+
+/// [`colorize`] for `text` continuing `before`, like a line of input after the lines entered so far.
+pub fn colorize_after(before: &str, text: &str) -> String {
+    let offset = before.chars().count();
+    let chars: Vec<char> = text.chars().collect();
+    let mut colored = String::new();
+    let mut pos = 0;
+
+    for span in classify(&format!("{}{}", before, text)) {
+        let color = match span.class.color() {
+            Some(color) if span.end > offset => color,
+            _ => continue,
+        };
+        let span = ClassifiedSpan { start: span.start.max(offset) - offset, end: span.end - offset, class: span.class };
+
+        colored.extend(&chars[pos..span.start]);
+        colored += color;
+        colored.extend(&chars[span.start..span.end]);
+        colored += "\x1b[0m";
+        pos = span.end
+    }
+    colored.extend(&chars[pos..]);
+
+    colored
+}
\ No newline at end of file
@@ -0,0 +1,651 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
+
+/// Results wider than this many bits are refused instead of exhausting memory.
+pub const MAX_BITS: u64 = 1 << 20;
+
+/// Integer of unbounded size, stored inline while it fits into an i128.
+///
+/// `Big` holds the sign (`true` for negative) and the magnitude as little-endian base 2^32 limbs,
+/// it's only used for values outside of the i128 range and never has trailing zero limbs.
+#[derive(Clone)]
+pub enum Integer {
+    Small(i128),
+    Big(bool, Vec<u32>)
+}
+
+impl Integer {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        trim(&mut magnitude);
+
+        if magnitude.len() <= 4 {
+            let value = magnitude.iter().rev().fold(0u128, |value, limb| value << 32 | *limb as u128);
+            if !negative && value <= i128::MAX as u128 {
+                return Integer::Small(value as i128);
+            } else if negative && value <= i128::MIN.unsigned_abs() {
+                return Integer::Small((value as i128).wrapping_neg());
+            }
+        }
+
+        Integer::Big(negative, magnitude)
+    }
+
+    fn to_parts(&self) -> (bool, Vec<u32>) {
+        match self {
+            Integer::Small(i) => {
+                let value = i.unsigned_abs();
+                let mut magnitude = vec![value as u32, (value >> 32) as u32, (value >> 64) as u32, (value >> 96) as u32];
+                trim(&mut magnitude);
+
+                (*i < 0, magnitude)
+            },
+            Integer::Big(negative, magnitude) => (*negative, magnitude.clone()),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Integer::Small(0))
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Integer::Small(i) => *i < 0,
+            Integer::Big(negative, _) => *negative,
+        }
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        match self {
+            Integer::Small(i) => Some(*i),
+            Integer::Big(_, _) => None,
+        }
+    }
+
+    /// Number of bits of the magnitude.
+    pub fn bits(&self) -> u64 {
+        match self {
+            Integer::Small(i) => 128 - i.unsigned_abs().leading_zeros() as u64,
+            Integer::Big(_, magnitude) => bits(magnitude),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Integer::Small(i) => *i as f64,
+            Integer::Big(negative, magnitude) => {
+                let value = magnitude.iter().rev().fold(0.0, |value, limb| value * 4294967296.0 + *limb as f64);
+                if *negative { -value } else { value }
+            },
+        }
+    }
+
+    /// Truncates `f` towards zero, `None` for infinities and NaN.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+
+        let f = f.trunc();
+        if f.abs() < 2f64.powi(127) {
+            return Some(Integer::Small(f as i128));
+        }
+
+        // |f| >= 2^127, so it's a 53 bit mantissa shifted left by a positive exponent
+        let bits = f.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+
+        let magnitude = shl(&[mantissa as u32, (mantissa >> 32) as u32], exponent);
+        Some(Self::from_parts(f < 0.0, magnitude))
+    }
+
+    /// Parses non-empty `digits` of `radix` without a sign or a prefix.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        if let Ok(value) = i128::from_str_radix(digits, radix) {
+            return Some(Integer::Small(value));
+        }
+
+        let mut magnitude = Vec::new();
+        for digit in digits.chars() {
+            mul_small_add(&mut magnitude, radix, digit.to_digit(radix)?);
+        }
+
+        Some(Self::from_parts(false, magnitude))
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        if let (Integer::Small(a), Integer::Small(b)) = (self, other) {
+            if let Some(value) = a.checked_mul(*b) {
+                return Some(Integer::Small(value));
+            }
+        }
+
+        if self.bits() + other.bits() > MAX_BITS {
+            return None;
+        }
+
+        let (a_negative, a) = self.to_parts();
+        let (b_negative, b) = other.to_parts();
+        Some(Self::from_parts(a_negative != b_negative, mul(&a, &b)))
+    }
+
+    /// Division truncating towards zero, `None` when `other` is zero.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(quotient, _)| quotient)
+    }
+
+    /// Remainder with the sign of `self`, `None` when `other` is zero.
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(_, remainder)| remainder)
+    }
+
+    pub fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        if let (Integer::Small(a), Integer::Small(b)) = (self, other) {
+            if let (Some(quotient), Some(remainder)) = (a.checked_div(*b), a.checked_rem(*b)) {
+                return Some((Integer::Small(quotient), Integer::Small(remainder)));
+            }
+        }
+
+        let (a_negative, a) = self.to_parts();
+        let (b_negative, b) = other.to_parts();
+        let (quotient, remainder) = div_rem(&a, &b);
+
+        Some((Self::from_parts(a_negative != b_negative, quotient), Self::from_parts(a_negative, remainder)))
+    }
+
+    /// `None` when the result would be wider than [`MAX_BITS`].
+    pub fn checked_pow(&self, exp: &Self) -> Option<Self> {
+        match self {
+            Integer::Small(0 | 1) => return Some(if exp.is_zero() { Integer::Small(1) } else { self.clone() }),
+            Integer::Small(-1) => return Some(Integer::Small(if exp.is_even() { 1 } else { -1 })),
+            _ => {}
+        }
+
+        let exp = exp.to_i128().filter(|exp| (0..=MAX_BITS as i128).contains(exp))? as u64;
+        if (self.bits() - 1).saturating_mul(exp) > MAX_BITS {
+            return None;
+        }
+
+        let mut result = Integer::Small(1);
+        let mut base = self.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul(&base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+
+        Some(result)
+    }
+
+    pub fn abs(&self) -> Self {
+        if self.is_negative() { -self } else { self.clone() }
+    }
+
+    /// Greatest common divisor, always non-negative.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while let Some(remainder) = a.checked_rem(&b) {
+            a = b;
+            b = remainder;
+        }
+
+        a
+    }
+
+    /// `10 ^ exp`, `None` when it would be wider than [`MAX_BITS`].
+    pub fn pow10(exp: u64) -> Option<Self> {
+        Integer::Small(10).checked_pow(&Integer::from(exp as i128))
+    }
+
+    /// Division rounding towards negative infinity, `None` when `other` is zero.
+    pub fn checked_floor_div(&self, other: &Self) -> Option<Self> {
+        let (quotient, remainder) = self.checked_div_rem(other)?;
+        if !remainder.is_zero() && remainder.is_negative() != other.is_negative() {
+            return Some(&quotient - &Integer::Small(1));
+        }
+
+        Some(quotient)
+    }
+
+    pub fn bit_and(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a & b, |a, b| a & b)
+    }
+
+    pub fn bit_or(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a | b, |a, b| a | b)
+    }
+
+    pub fn bit_xor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a ^ b, |a, b| a ^ b)
+    }
+
+    pub fn bit_not(&self) -> Self {
+        &-self - &Integer::Small(1)
+    }
+
+    /// Bitwise operations behave as if negative numbers were in two's complement with infinitely many sign bits.
+    fn bitwise(&self, other: &Self, small: fn(i128, i128) -> i128, limb: fn(u32, u32) -> u32) -> Self {
+        if let (Integer::Small(a), Integer::Small(b)) = (self, other) {
+            return Integer::Small(small(*a, *b));
+        }
+
+        let len = self.bits().max(other.bits()) as usize / 32 + 2;
+        let limbs = self.twos(len).iter().zip(other.twos(len)).map(|(a, b)| limb(*a, b)).collect();
+        Self::from_twos(limbs)
+    }
+
+    /// Two's complement in `len` limbs, `len` must leave room for the sign bit.
+    fn twos(&self, len: usize) -> Vec<u32> {
+        let (negative, mut limbs) = self.to_parts();
+        limbs.resize(len, 0);
+
+        if negative {
+            for limb in limbs.iter_mut() {
+                *limb = !*limb
+            }
+            increment(&mut limbs);
+        }
+
+        limbs
+    }
+
+    fn from_twos(mut limbs: Vec<u32>) -> Self {
+        let negative = limbs.last().is_some_and(|last| last >> 31 == 1);
+
+        if negative {
+            for limb in limbs.iter_mut() {
+                *limb = !*limb
+            }
+            increment(&mut limbs);
+        }
+
+        Self::from_parts(negative, limbs)
+    }
+
+    /// `self * 2^shift`, `None` when the result would be wider than [`MAX_BITS`].
+    pub fn checked_shl(&self, shift: u64) -> Option<Self> {
+        if self.is_zero() {
+            return Some(Integer::Small(0));
+        }
+
+        let bits = self.bits().saturating_add(shift);
+        match self {
+            Integer::Small(i) if bits < 127 => Some(Integer::Small(i << shift)),
+            _ if bits > MAX_BITS => None,
+            _ => {
+                let (negative, magnitude) = self.to_parts();
+                Some(Self::from_parts(negative, shl(&magnitude, shift)))
+            }
+        }
+    }
+
+    /// `self / 2^shift` rounded towards negative infinity, so `-1 >> 1` stays `-1`.
+    pub fn shr(&self, shift: u64) -> Self {
+        match self {
+            Integer::Small(i) => Integer::Small(i >> shift.min(127)),
+            Integer::Big(false, magnitude) => Self::from_parts(false, shr(magnitude, shift)),
+            Integer::Big(true, _) => {
+                let (_, magnitude) = (&self.abs() - &Integer::Small(1)).to_parts();
+                (&-&Self::from_parts(false, shr(&magnitude, shift))) - &Integer::Small(1)
+            }
+        }
+    }
+
+    pub fn is_even(&self) -> bool {
+        match self {
+            Integer::Small(i) => i % 2 == 0,
+            Integer::Big(_, magnitude) => magnitude[0] % 2 == 0,
+        }
+    }
+}
+
+impl From<i128> for Integer {
+    fn from(value: i128) -> Self {
+        Integer::Small(value)
+    }
+}
+
+impl Add for &Integer {
+    type Output = Integer;
+
+    fn add(self, other: &Integer) -> Integer {
+        if let (Integer::Small(a), Integer::Small(b)) = (self, other) {
+            if let Some(value) = a.checked_add(*b) {
+                return Integer::Small(value);
+            }
+        }
+
+        let (a_negative, a) = self.to_parts();
+        let (b_negative, b) = other.to_parts();
+
+        if a_negative == b_negative {
+            return Integer::from_parts(a_negative, add(&a, &b));
+        }
+
+        match cmp(&a, &b) {
+            Ordering::Less => Integer::from_parts(b_negative, sub(&b, &a)),
+            _ => Integer::from_parts(a_negative, sub(&a, &b)),
+        }
+    }
+}
+
+impl Sub for &Integer {
+    type Output = Integer;
+
+    fn sub(self, other: &Integer) -> Integer {
+        self + &-other
+    }
+}
+
+impl Neg for &Integer {
+    type Output = Integer;
+
+    fn neg(self) -> Integer {
+        match self {
+            Integer::Small(i) => match i.checked_neg() {
+                Some(value) => Integer::Small(value),
+                None => Integer::from_parts(false, self.to_parts().1),
+            },
+            Integer::Big(negative, magnitude) => Integer::from_parts(!negative, magnitude.clone()),
+        }
+    }
+}
+
+impl PartialEq for Integer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Integer {}
+
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if let (Integer::Small(a), Integer::Small(b)) = (self, other) {
+            return a.cmp(b);
+        }
+
+        let (a_negative, a) = self.to_parts();
+        let (b_negative, b) = other.to_parts();
+
+        match (a_negative, b_negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp(&a, &b),
+            (true, true) => cmp(&b, &a),
+        }
+    }
+}
+
+impl FromStr for Integer {
+    type Err = String;
+
+    /// Parses decimal digits with an optional leading `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("{} isn\'t a decimal integer", s));
+        }
+
+        if let Ok(value) = s.parse::<i128>() {
+            return Ok(Integer::Small(value));
+        }
+
+        let mut magnitude = Vec::new();
+        for chunk in digits.as_bytes().chunks(9) {
+            let value = chunk.iter().fold(0u32, |value, digit| value * 10 + (digit - b'0') as u32);
+            mul_small_add(&mut magnitude, 10u32.pow(chunk.len() as u32), value);
+        }
+
+        Ok(Self::from_parts(negative, magnitude))
+    }
+}
+
+impl Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (negative, mut magnitude) = match self {
+            Integer::Small(i) => return write!(f, "{}", i),
+            Integer::Big(negative, magnitude) => (*negative, magnitude.clone()),
+        };
+
+        let mut chunks = Vec::new();
+        while !magnitude.is_empty() {
+            chunks.push(div_small(&mut magnitude, 1_000_000_000));
+        }
+
+        let mut digits = if negative { "-".to_string() } else { String::new() };
+        digits.push_str(&chunks.pop().unwrap_or(0).to_string());
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+
+        f.write_str(&digits)
+    }
+}
+
+impl Debug for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn bits(magnitude: &[u32]) -> u64 {
+    magnitude.last().map_or(0, |last| magnitude.len() as u64 * 32 - last.leading_zeros() as u64)
+}
+
+fn cmp(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    result.push(carry as u32);
+
+    trim(&mut result);
+    result
+}
+
+/// `a` must not be smaller than `b`.
+fn sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+
+    for (i, &limb) in a.iter().enumerate() {
+        let difference = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        result.push(difference as u32);
+        borrow = (difference < 0) as i64;
+    }
+
+    trim(&mut result);
+    result
+}
+
+fn mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0u32; a.len() + b.len()];
+
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let product = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+
+    trim(&mut result);
+    result
+}
+
+fn mul_small_add(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+
+    for limb in magnitude.iter_mut() {
+        let product = *limb as u64 * factor as u64 + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divides `magnitude` in place and returns the remainder.
+fn div_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+
+    for limb in magnitude.iter_mut().rev() {
+        let value = remainder << 32 | *limb as u64;
+        *limb = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+
+    trim(magnitude);
+    remainder as u32
+}
+
+fn shl(magnitude: &[u32], shift: u64) -> Vec<u32> {
+    let limbs = (shift / 32) as usize;
+    let bits = (shift % 32) as u32;
+
+    let mut result = vec![0u32; limbs];
+    let mut carry = 0u32;
+    for &limb in magnitude {
+        if bits == 0 {
+            result.push(limb);
+        } else {
+            result.push(limb << bits | carry);
+            carry = limb >> (32 - bits);
+        }
+    }
+    result.push(carry);
+
+    trim(&mut result);
+    result
+}
+
+fn shr(magnitude: &[u32], shift: u64) -> Vec<u32> {
+    let limbs = usize::try_from(shift / 32).unwrap_or(usize::MAX);
+    let bits = (shift % 32) as u32;
+    let rest = magnitude.get(limbs..).unwrap_or(&[]);
+
+    let mut result: Vec<u32> = (0..rest.len()).map(|i| match bits {
+        0 => rest[i],
+        _ => rest[i] >> bits | rest.get(i + 1).map_or(0, |next| next << (32 - bits)),
+    }).collect();
+
+    trim(&mut result);
+    result
+}
+
+/// Adds one in place, a carry out of the last limb is dropped.
+fn increment(limbs: &mut [u32]) {
+    for limb in limbs.iter_mut() {
+        let (value, overflow) = limb.overflowing_add(1);
+        *limb = value;
+        if !overflow {
+            break
+        }
+    }
+}
+
+/// Long division of magnitudes (Knuth, TAOCP vol. 2, algorithm D), `b` must not be zero.
+fn div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    if b.len() == 1 {
+        let mut quotient = a.to_vec();
+        let remainder = div_small(&mut quotient, b[0]);
+        return (quotient, if remainder == 0 { Vec::new() } else { vec![remainder] });
+    }
+
+    // normalize so the top limb of the divisor has its high bit set
+    let shift = b[b.len() - 1].leading_zeros();
+    let v = shl(b, shift as u64);
+    let mut u = shl(a, shift as u64);
+    u.resize(a.len() + 1, 0);
+
+    let n = v.len();
+    let m = u.len() - n - 1;
+    let mut quotient = vec![0u32; m + 1];
+
+    for j in (0..=m).rev() {
+        let numerator = (u[j + n] as u64) << 32 | u[j + n - 1] as u64;
+        let mut qhat = numerator / v[n - 1] as u64;
+        let mut rhat = numerator % v[n - 1] as u64;
+
+        while qhat >> 32 != 0 || qhat * v[n - 2] as u64 > (rhat << 32 | u[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += v[n - 1] as u64;
+            if rhat >> 32 != 0 {
+                break
+            }
+        }
+
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = qhat * v[i] as u64 + carry;
+            carry = product >> 32;
+            let difference = u[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            u[i + j] = difference as u32;
+            borrow = (difference < 0) as i64;
+        }
+        let difference = u[j + n] as i64 - borrow - carry as i64;
+        u[j + n] = difference as u32;
+
+        if difference < 0 {
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+
+        quotient[j] = qhat as u32;
+    }
+
+    u.truncate(n);
+    trim(&mut quotient);
+    (quotient, shr(&u, shift as u64))
+}
\ No newline at end of file
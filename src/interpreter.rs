@@ -1,28 +1,64 @@
-use std::io;
+use std::{fs, io};
+use std::rc::Rc;
 
 use crate::{
     lexer::Lexer,
+    builtin::Builtin,
+    variable::Variable,
     parser::Parser,
     types::Type,
     token::Token,
-    context::Context
+    context::Context,
+    lint::Linter,
+    node::Node,
+    source::Source,
+    error_code::ErrorCode
 };
 
 pub struct Interpreter<'c> {
+    name: String,
     text: String,
     context: Context<'c>,
-    tokens: Vec<Token>
+    tokens: Vec<Token>,
+    interactive: bool,
+    deny_warnings: bool
 }
 impl<'c> Interpreter<'c> {
-    pub fn new(text: &str) -> io::Result<Self> {
+    /// `name` is the file the text comes from, it's shown in tracebacks.
+    pub fn new(name: &str, text: &str) -> io::Result<Self> {
         let mut lexer = Lexer::new(text);
-        let context = Context::new("<program>", None, 1);
 
-        Ok(Self { text: text.to_string(), tokens: lexer.tokenize()?, context })
+        Ok(Self {
+            name: name.to_string(),
+            text: text.to_string(),
+            tokens: lexer.tokenize()?,
+            context: Self::global_context(name, text),
+            interactive: false,
+            deny_warnings: false
+        })
+    }
+
+    fn global_context(name: &str, text: &str) -> Context<'c> {
+        let mut context = Context::new("<program>", None, Rc::new(Source::new(name, text)), 0);
+        for builtin in Builtin::builtins() {
+            context.get_symbol_table_mut().declare(Variable::new(builtin.get_name(), Type::Builtin(builtin)));
+        }
+
+        context
+    }
+
+    /// Interactive sessions don't report unused top-level variables, a later input may still read them.
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive
+    }
+
+    pub fn set_deny_warnings(&mut self, deny_warnings: bool) {
+        self.deny_warnings = deny_warnings
     }
     
     pub fn update(&mut self, text: &str) -> io::Result<()> {
         self.text = text.to_string();
+        self.context.set_source(Rc::new(Source::new(&self.name, text)));
 
         let mut lexer = Lexer::new(text);
         self.tokens = lexer.tokenize()?;
@@ -33,11 +69,67 @@ impl<'c> Interpreter<'c> {
     pub fn run(&mut self) -> io::Result<Type> {
         let mut parser = Parser::new(&self.tokens);
 
-        let node = parser.parse().map_err(|err| err.format(&self.text)).map_err(|err| io::Error::new(
-            err.kind(),
-            format!("Traceback:\n{}{}", self.context.generate_traceback(), err)
-        ))?;
+        let node = parser.parse().map_err(|err| err.format(&self.text))?;
+
+        self.lint(&node)?;
+
+        node.get_value(&mut self.context).map_err(|err| self.context.trace(err))
+    }
+
+    /// Variables the session declared, builtins aren't included.
+    pub fn get_variables(&self) -> Vec<&Variable> {
+        let mut variables: Vec<&Variable> = self.context.get_symbol_table().get_variables().into_iter()
+            .filter(|variable| !matches!(variable.get_value(), Type::Builtin(_)))
+            .collect();
+        variables.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        variables
+    }
+
+    /// Evaluates `text` on a copy of the session, so variables it declares or changes don't stay.
+    pub fn evaluate_isolated(&self, text: &str) -> io::Result<Type> {
+        let tokens = Lexer::new(text).tokenize()?;
+        let node = Parser::new(&tokens).parse().map_err(|err| err.format(text))?;
+
+        let mut context = self.context.clone();
+        context.set_source(Rc::new(Source::new(&self.name, text)));
+        node.get_value(&mut context).map_err(|err| context.trace(err))
+    }
+
+    /// Forgets every variable the session declared.
+    pub fn reset(&mut self) {
+        self.context = Self::global_context(&self.name, "");
+        self.text.clear();
+        self.tokens.clear();
+    }
+
+    /// Runs the file at `path` in the session, what it declares stays declared.
+    pub fn load(&mut self, path: &str) -> io::Result<Type> {
+        let text = fs::read_to_string(path).map_err(|err| io::Error::new(err.kind(), format!("can\'t read {}: {}", path, err)))?;
+        let name = std::mem::replace(&mut self.name, path.to_string());
+
+        let result = self.update(&text).and_then(|_| self.run());
+        self.name = name;
 
-        node.get_value(&mut self.context)
+        result
+    }
+
+    fn lint(&self, node: &Node) -> io::Result<()> {
+        let warnings = Linter::new(!self.interactive).lint(node);
+        let formatted = warnings.iter().map(|warning| warning.format(&self.text)).collect::<Vec<String>>();
+
+        if self.deny_warnings && !formatted.is_empty() {
+            return Err(ErrorCode::DeniedWarning.error(format!(
+                "aborting due to {} warning(s)\n{}",
+                formatted.len(),
+                formatted.join("\n")
+            )));
+        }
+
+        for warning in formatted {
+            eprintln!("{}", warning)
+        }
+
+        Ok(())
     }
 }
\ No newline at end of file
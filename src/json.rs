@@ -0,0 +1,281 @@
+use std::io;
+
+/// A JSON value, enough of it for the messages `kumi lsp` exchanges with editors.
+#[derive(Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Array(Vec<Json>),
+    /// Fields keep the order they were written or built in.
+    Object(Vec<(String, Json)>)
+}
+
+impl Json {
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut reader = Reader { chars: &chars, pos: 0 };
+
+        let value = reader.value()?;
+        reader.skip_whitespace();
+        if reader.pos < chars.len() {
+            return Err(reader.error("unexpected text after the value"));
+        }
+
+        Ok(value)
+    }
+
+    pub fn object(fields: Vec<(&str, Json)>) -> Self {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    /// The field `key` of an object, `None` for other values and missing fields.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(string) => Some(string),
+            _ => None
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(number) if *number >= 0.0 && number.fract() == 0.0 => Some(*number as usize),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::Str(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::Str(value)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as f64)
+    }
+}
+
+impl ToString for Json {
+    fn to_string(&self) -> String {
+        match self {
+            Json::Null => "null".to_string(),
+            Json::Bool(value) => value.to_string(),
+            Json::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => format!("{}", *number as i64),
+            Json::Number(number) if number.is_finite() => number.to_string(),
+            Json::Number(_) => "null".to_string(),
+            Json::Str(string) => quote(string),
+            Json::Array(items) => format!("[{}]", items.iter().map(|item| item.to_string()).collect::<Vec<_>>().join(",")),
+            Json::Object(fields) => format!(
+                "{{{}}}",
+                fields.iter().map(|(key, value)| format!("{}:{}", quote(key), value.to_string())).collect::<Vec<_>>().join(",")
+            ),
+        }
+    }
+}
+
+fn quote(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c if (c as u32) < 0x20 => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+struct Reader<'c> {
+    chars: &'c [char],
+    pos: usize
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("invalid JSON at {}: {}", self.pos, message))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| " \t\r\n".contains(*c)) {
+            self.pos += 1
+        }
+    }
+
+    fn expect(&mut self, c: char) -> io::Result<()> {
+        self.skip_whitespace();
+        if self.chars.get(self.pos) != Some(&c) {
+            return Err(self.error(&format!("expected \'{}\'", c)));
+        }
+
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> io::Result<Json> {
+        let word: Vec<char> = word.chars().collect();
+        if !self.chars[self.pos..].starts_with(&word) {
+            return Err(self.error("unknown literal"));
+        }
+
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> io::Result<Json> {
+        self.skip_whitespace();
+
+        match self.chars.get(self.pos) {
+            Some('n') => self.literal("null", Json::Null),
+            Some('t') => self.literal("true", Json::Bool(true)),
+            Some('f') => self.literal("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::Str),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.close(']') {
+                    loop {
+                        items.push(self.value()?);
+                        if self.close(']') {
+                            break
+                        }
+                        self.expect(',')?
+                    }
+                }
+
+                Ok(Json::Array(items))
+            },
+            Some('{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if !self.close('}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        fields.push((key, self.value()?));
+                        if self.close('}') {
+                            break
+                        }
+                        self.expect(',')?
+                    }
+                }
+
+                Ok(Json::Object(fields))
+            },
+            Some(c) if *c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// Steps over `close` if it's the next char after whitespace.
+    fn close(&mut self, close: char) -> bool {
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&close) {
+            self.pos += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn number(&mut self) -> io::Result<Json> {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+            self.pos += 1
+        }
+
+        self.chars[start..self.pos].iter().collect::<String>().parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        if self.chars.get(self.pos) != Some(&'"') {
+            return Err(self.error("expected a string"));
+        }
+        self.pos += 1;
+
+        let mut string = String::new();
+        loop {
+            let c = *self.chars.get(self.pos).ok_or_else(|| self.error("string isn\'t closed"))?;
+            self.pos += 1;
+
+            match c {
+                '"' => return Ok(string),
+                '\\' => {
+                    let escaped = *self.chars.get(self.pos).ok_or_else(|| self.error("string isn\'t closed"))?;
+                    self.pos += 1;
+                    match escaped {
+                        'n' => string.push('\n'),
+                        'r' => string.push('\r'),
+                        't' => string.push('\t'),
+                        'b' => string.push('\u{8}'),
+                        'f' => string.push('\u{c}'),
+                        'u' => string.push(self.unicode_escape()?),
+                        c => string.push(c),
+                    }
+                },
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// The char of a `\uXXXX` escape, chars outside the basic plane are written as two of them.
+    fn unicode_escape(&mut self) -> io::Result<char> {
+        let high = self.hex()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"));
+        }
+
+        if !self.chars[self.pos..].starts_with(&['\\', 'u']) {
+            return Err(self.error("expected the second half of a surrogate pair"));
+        }
+        self.pos += 2;
+        let low = self.hex()?;
+
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF))
+            .ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex(&mut self) -> io::Result<u32> {
+        let digits: String = self.chars.get(self.pos..self.pos + 4).ok_or_else(|| self.error("invalid unicode escape"))?.iter().collect();
+        self.pos += 4;
+
+        u32::from_str_radix(&digits, 16).map_err(|_| self.error("invalid unicode escape"))
+    }
+}
\ No newline at end of file
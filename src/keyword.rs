@@ -2,13 +2,41 @@ use std::collections::HashMap;
 
 #[derive(Clone)]
 pub enum Keyword {
-    Let
+    Let,
+    Fn,
+    Return,
+    Is,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Match,
+    If,
+    Else,
+    For,
+    Break,
+    Continue,
+    Step
 }
 
 impl Keyword {
     fn keywords() -> Vec<Self> {
         vec![
-            Keyword::Let
+            Keyword::Let,
+            Keyword::Fn,
+            Keyword::Return,
+            Keyword::Is,
+            Keyword::Throw,
+            Keyword::Try,
+            Keyword::Catch,
+            Keyword::Finally,
+            Keyword::Match,
+            Keyword::If,
+            Keyword::Else,
+            Keyword::For,
+            Keyword::Break,
+            Keyword::Continue,
+            Keyword::Step
         ]
     }
 
@@ -28,6 +56,20 @@ impl ToString for Keyword {
     fn to_string(&self) -> String {
         match self {
             Keyword::Let => "let".to_string(),
+            Keyword::Fn => "fn".to_string(),
+            Keyword::Return => "return".to_string(),
+            Keyword::Is => "is".to_string(),
+            Keyword::Throw => "throw".to_string(),
+            Keyword::Try => "try".to_string(),
+            Keyword::Catch => "catch".to_string(),
+            Keyword::Finally => "finally".to_string(),
+            Keyword::Match => "match".to_string(),
+            Keyword::If => "if".to_string(),
+            Keyword::Else => "else".to_string(),
+            Keyword::For => "for".to_string(),
+            Keyword::Break => "break".to_string(),
+            Keyword::Continue => "continue".to_string(),
+            Keyword::Step => "step".to_string(),
         }
     }
 }
\ No newline at end of file
@@ -1,15 +1,15 @@
-use crate::{token::*, keyword::Keyword, types::Type, op::Op};
+use crate::{token::*, cst::{CstToken, attach_trivia}, keyword::Keyword, types::Type, op::Op, error_code::ErrorCode, decimal::Decimal, integer::Integer};
 
 use std::io;
 
-const DIGITS: &'static str = "0123456789";
-const NUM_SYMBOLS: &'static str = ".";
+const DIGITS: &str = "0123456789";
 
 pub struct Lexer {
     text: Vec<char>,
     pos: usize,
     line: usize,
     col: usize,
+    brackets: Vec<char>,
     cc: Option<char>
 }
 
@@ -17,11 +17,12 @@ impl Lexer {
     pub fn new(text: &str) -> Self {
         let text: Vec<char> = text.chars().collect();
         let pos = 0;
-        let cc = text.get(pos).map(|&c| c);
+        let cc = text.get(pos).copied();
         Self {
             text,
             line: 0,
             col: 0,
+            brackets: Vec::new(),
             pos,
             cc
         }
@@ -36,42 +37,236 @@ impl Lexer {
             self.col = 0
         }
 
-        self.cc = self.text.get(self.pos).map(|&c| c);
+        self.cc = self.text.get(self.pos).copied();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text.get(self.pos + 1).copied()
     }
 
     fn read_number(&mut self) -> io::Result<TT> {
-        let mut str = String::new();
-        let mut dots = 0;
+        if self.cc == Some('0') {
+            let radix = match self.peek() {
+                Some('x' | 'X') => Some((16, "hex")),
+                Some('o' | 'O') => Some((8, "octal")),
+                Some('b' | 'B') => Some((2, "binary")),
+                _ => None
+            };
+
+            if let Some((radix, name)) = radix {
+                self.step();
+                self.step();
+                return self.read_radix_number(radix, name);
+            }
+        }
 
-        while let Some(cc) = self.cc {
-            if !DIGITS.contains(cc) && !NUM_SYMBOLS.contains(cc) {
-                break
+        let mut str = self.read_digits(10, "decimal")?;
+        let mut float = false;
+
+        // a dot followed by another dot, a letter or `_` isn't part of the number, `1.` alone is a float
+        if self.cc == Some('.') && !matches!(self.peek(), Some(c) if c == '.' || c == '_' || c.is_alphabetic()) {
+            float = true;
+            str.push('.');
+            self.step();
+
+            if let Some('0'..='9') = self.cc {
+                str.push_str(&self.read_digits(10, "decimal")?);
             }
 
-            if cc == '.' {
-                if dots == 1 {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput, format!("can\'t float can have only 1 dot can\'t add one more to {}", str)
-                    ));
-                }
-                dots += 1;
+            if self.cc == Some('.') && matches!(self.peek(), Some('0'..='9')) {
+                return Err(ErrorCode::MalformedNumber.error(
+                    format!("can\'t add one more dot to {}, a number can have only 1 dot", str)
+                ));
             }
+        }
 
-            str.push(cc);
-            self.step()
+        if let Some('e' | 'E') = self.cc {
+            float = true;
+            str.push('e');
+            self.step();
+
+            if let Some(sign @ ('+' | '-')) = self.cc {
+                str.push(sign);
+                self.step();
+            }
+
+            match self.cc {
+                Some('0'..='9') => str.push_str(&self.read_digits(10, "exponent")?),
+                _ => return Err(ErrorCode::MalformedNumber.error(format!("expected a digit in the exponent of {}", str))),
+            }
         }
 
-        if dots == 0 {
+        if let Some(suffix @ ('r' | 'd')) = self.cc {
+            self.step();
+            self.check_number_end("decimal")?;
+            let decimal: Decimal = str.parse().map_err(
+                |err| ErrorCode::MalformedNumber.error(format!("can\'t parse {}{} due to {}", str, suffix, err))
+            )?;
+
+            if suffix == 'd' {
+                return Ok(TT::Type(Type::Decimal(decimal)));
+            }
+            return decimal.to_rational().map(|rational| TT::Type(Type::Rational(rational))).ok_or_else(
+                || ErrorCode::MalformedNumber.error(format!("{}r has too many digits", str))
+            );
+        }
+
+        self.check_number_end("decimal")?;
+
+        if !float {
             return Ok(TT::Type(Type::Int(str.parse().map_err(
-                |err| io::Error::new(io::ErrorKind::InvalidInput, format!("can\'t parse {} as i128 due to {}", str, err))
+                |err| ErrorCode::MalformedNumber.error(format!("can\'t parse {} as int due to {}", str, err))
             )?)))
         }
         
         Ok(TT::Type(Type::Float(str.parse().map_err(
-            |err| io::Error::new(io::ErrorKind::InvalidInput, format!("can\'t parse {} as f64 due to {}", str, err))
+            |err| ErrorCode::MalformedNumber.error(format!("can\'t parse {} as f64 due to {}", str, err))
         )?)))
     }
 
+    /// Reads the digits following a `0x`, `0o` or `0b` prefix.
+    fn read_radix_number(&mut self, radix: u32, name: &str) -> io::Result<TT> {
+        match self.cc {
+            Some(cc) if cc.is_digit(radix) => {},
+            Some(cc) if cc.is_alphanumeric() || cc == '_' => return Err(ErrorCode::MalformedNumber.error(
+                format!("invalid digit \'{}\' in {} literal", cc, name)
+            )),
+            _ => return Err(ErrorCode::MalformedNumber.error(format!("expected {} digits after the prefix", name))),
+        }
+
+        let digits = self.read_digits(radix, name)?;
+        if self.cc == Some('.') && matches!(self.peek(), Some('0'..='9')) {
+            return Err(ErrorCode::MalformedNumber.error(format!("{} literals can\'t have a fractional part", name)));
+        }
+        self.check_number_end(name)?;
+
+        Integer::from_str_radix(&digits, radix).map(|int| TT::Type(Type::Int(int))).ok_or_else(
+            || ErrorCode::MalformedNumber.error(format!("can\'t parse {} as {} int", digits, name))
+        )
+    }
+
+    /// Reads digits of `radix` starting at the current char, single `_` may separate them and are dropped.
+    fn read_digits(&mut self, radix: u32, name: &str) -> io::Result<String> {
+        let mut digits = String::new();
+
+        while let Some(cc) = self.cc {
+            if cc == '_' {
+                match self.peek() {
+                    Some(next) if next.is_digit(radix) => {},
+                    Some(next) if next.is_alphanumeric() => {
+                        self.step();
+                        return Err(ErrorCode::MalformedNumber.error(format!("invalid digit \'{}\' in {} literal", next, name)));
+                    },
+                    _ => return Err(ErrorCode::MalformedNumber.error("\'_\' in a number must be placed between two digits")),
+                }
+            } else if cc.is_digit(radix) {
+                digits.push(cc);
+            } else {
+                break
+            }
+
+            self.step()
+        }
+
+        Ok(digits)
+    }
+
+    /// A letter, a digit or `_` right after a number literal is a typo like `0b102` rather than a new token.
+    fn check_number_end(&self, name: &str) -> io::Result<()> {
+        match self.cc {
+            Some(cc) if cc.is_alphanumeric() || cc == '_' => Err(ErrorCode::MalformedNumber.error(
+                format!("invalid digit \'{}\' in {} literal", cc, name)
+            )),
+            _ => Ok(())
+        }
+    }
+
+    /// Reads a `"` quoted string, `\` escapes `n`, `t`, `r`, `0`, `\` itself and `"`.
+    fn read_string(&mut self) -> io::Result<TT> {
+        let (pos, line, col) = (self.pos, self.line, self.col);
+        let mut string = String::new();
+        self.step();
+
+        loop {
+            match self.cc {
+                Some('"') => break,
+                Some('\\') => {
+                    self.step();
+                    string.push(match self.cc {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some(cc @ ('\\' | '"')) => cc,
+                        Some(cc) => return Err(ErrorCode::InvalidEscape.error(format!("unknown escape sequence \'\\{}\'", cc))),
+                        None => continue,
+                    })
+                },
+                Some(cc) => string.push(cc),
+                None => {
+                    // point at the opening quote, the end of the input says nothing about where the string was meant to end
+                    (self.pos, self.line, self.col) = (pos, line, col);
+                    return Err(ErrorCode::UnterminatedString.error("string isn\'t closed, expected \'\"\'"));
+                },
+            }
+
+            self.step()
+        }
+
+        self.step();
+        Ok(TT::Type(Type::Str(string)))
+    }
+
+    fn skip_line_comment(&mut self) {
+        while !matches!(self.cc, Some('\n') | None) {
+            self.step()
+        }
+    }
+
+    /// `/* ... */`, comments inside it nest so a block with comments can be commented out as a whole.
+    fn skip_block_comment(&mut self) -> io::Result<()> {
+        let (pos, line, col) = (self.pos, self.line, self.col);
+        let mut depth = 0;
+
+        loop {
+            match (self.cc, self.peek()) {
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.step();
+                },
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.step();
+                    if depth == 0 {
+                        self.step();
+                        return Ok(());
+                    }
+                },
+                (Some(_), _) => {},
+                (None, _) => {
+                    (self.pos, self.line, self.col) = (pos, line, col);
+                    return Err(ErrorCode::UnterminatedComment.error("block comment isn\'t closed, expected \'*/\'"));
+                },
+            }
+
+            self.step()
+        }
+    }
+
+    /// `///` followed by anything but another `/` documents the declaration below it,
+    /// `////` and longer runs of slashes start ordinary comments.
+    fn is_doc_comment(&self) -> bool {
+        self.text[self.pos..].starts_with(&['/', '/', '/']) && self.text.get(self.pos + 3) != Some(&'/')
+    }
+
+    fn read_doc_comment(&mut self) -> TT {
+        let start = self.pos + 3;
+        self.skip_line_comment();
+
+        let text: String = self.text[start..self.pos].iter().collect();
+        TT::Doc(text.strip_prefix(' ').unwrap_or(&text).trim_end().to_string())
+    }
+
     fn read_indenifier(&mut self) -> TT {
         let mut indenifier = String::new();
 
@@ -90,7 +285,15 @@ impl Lexer {
             return TT::Keyword(keyword.clone())
         }
 
-        return TT::Indenifier(indenifier);
+        match indenifier.as_str() {
+            "true" => return TT::Type(Type::Bool(true)),
+            "false" => return TT::Type(Type::Bool(false)),
+            "none" => return TT::Type(Type::None),
+            "in" => return TT::Op(Op::In),
+            _ => {}
+        }
+
+        TT::Indenifier(indenifier)
     }
 
     fn read_lt(&mut self) -> TT {
@@ -99,6 +302,9 @@ impl Lexer {
         if let Some('=') = self.cc {
             self.step();
             return TT::Op(Op::LTE);
+        } else if let Some('<') = self.cc {
+            self.step();
+            return TT::Op(Op::Shl);
         }
 
         TT::Op(Op::LT)
@@ -110,6 +316,9 @@ impl Lexer {
         if let Some('=') = self.cc {
             self.step();
             return TT::Op(Op::GTE);
+        } else if let Some('>') = self.cc {
+            self.step();
+            return TT::Op(Op::Shr);
         }
 
         TT::Op(Op::GT)
@@ -134,10 +343,7 @@ impl Lexer {
             return Ok(TT::Op(Op::Or));
         }
 
-        Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "expected \'|\'"
-        ))
+        Ok(TT::Op(Op::BitOr))
     }
 
     fn read_and(&mut self) -> io::Result<TT> {
@@ -148,21 +354,29 @@ impl Lexer {
             return Ok(TT::Op(Op::And));
         }
 
-        Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "expected \'&\'"
-        ))
+        Ok(TT::Op(Op::BitAnd))
     }
 
     fn read_eq(&mut self) -> TT {
         self.step();
 
-        if let Some('=') = self.cc {
-            self.step();
-            return TT::Op(Op::Eq);
+        match self.cc {
+            Some('=') => {
+                self.step();
+                TT::Op(Op::Eq)
+            },
+            Some('>') => {
+                self.step();
+                TT::ARROW
+            },
+            _ => TT::EQ
         }
+    }
 
-        return TT::EQ;
+    fn close_bracket(&mut self, open: char) {
+        if let Some(position) = self.brackets.iter().rposition(|bracket| bracket == &open) {
+            self.brackets.truncate(position);
+        }
     }
 
     pub fn tokenize(&mut self) -> io::Result<Vec<Token>> {
@@ -170,83 +384,177 @@ impl Lexer {
         
         result.map_err(|err| {
             let line = get_line_by_char_index(&self.text, self.pos).unwrap_or(&[]);
-            let pointers = line.into_iter().map(|_| '^').collect::<String>();
+            let pointers = " ".repeat(self.col) + "^";
 
             io::Error::new(
                 err.kind(),
                 format!("...\n{}\n{}\nLexer error on line - {}, column - {}: {}\n...",
-                line.into_iter().collect::<String>(), pointers, self.line+1, self.col+1, err.to_string())
+                line.iter().collect::<String>(), pointers, self.line+1, self.col+1, err)
             )
         })
     }
 
+    /// Like [`Self::tokenize`], but a failure comes back as is, with the index of the char it happened at.
+    pub fn try_tokenize(&mut self) -> Result<Vec<Token>, (usize, io::Error)> {
+        self._tokenize().map_err(|err| (self.pos, err))
+    }
+
+    /// Like [`Self::tokenize`], but each token keeps its source text and the trivia in front of it.
+    pub fn tokenize_lossless(&mut self) -> io::Result<Vec<CstToken>> {
+        let tokens = self.tokenize()?;
+
+        Ok(attach_trivia(&self.text, tokens))
+    }
+
     fn _tokenize(&mut self) -> io::Result<Vec<Token>> {
         let mut tokens = Vec::new();
 
         while let Some(cc) = self.cc {
-            if " \t".contains(cc) {
+            // new lines inside parentheses and square brackets don't end a statement, inside braces they do
+            if " \t\r".contains(cc) || (cc == '\n' && matches!(self.brackets.last(), Some('(' | '['))) {
                 self.step();
                 continue
             } else if DIGITS.contains(cc) {
-                let start = self.pos.clone();
+                let start = self.pos;
                 let number = self.read_number()?;
                 tokens.push(Token::new(start, self.pos, number));
                 continue
+            } else if cc == '"' {
+                let start = self.pos;
+                let string = self.read_string()?;
+                tokens.push(Token::new(start, self.pos, string));
+                continue
             } else if cc.is_alphabetic() || cc == '_' {
-                let start = self.pos.clone();
+                let start = self.pos;
                 let indenifier = self.read_indenifier();
                 tokens.push(Token::new(start, self.pos, indenifier));
                 continue
             }
 
             match cc {
+                '#' => {
+                    self.skip_line_comment();
+                    continue
+                },
+                '/' if self.peek() == Some('*') => {
+                    self.skip_block_comment()?;
+                    continue
+                },
+                // inside parentheses a doc comment has no declaration to document
+                '/' if self.is_doc_comment() && !matches!(self.brackets.last(), Some('(' | '[')) => {
+                    let start = self.pos;
+                    let doc = self.read_doc_comment();
+                    tokens.push(Token::new(start, self.pos, doc));
+                    continue
+                },
+                '/' if self.text[self.pos..].starts_with(&['/', '/', '/']) => {
+                    self.skip_line_comment();
+                    continue
+                },
                 '+' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Add))),
                 '-' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Sub))),
+                '/' if self.peek() == Some('/') => {
+                    tokens.push(Token::new(self.pos, self.pos+2, TT::Op(Op::FloorDevd)));
+                    self.step()
+                },
                 '/' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Devd))),
                 '*' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Mult))),
                 '^' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Pow))),
                 '%' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Remain))),
-                '(' => tokens.push(Token::new(self.pos, self.pos+1, TT::LPR)),
-                ')' => tokens.push(Token::new(self.pos, self.pos+1, TT::RPR)),
+                '~' => tokens.push(Token::new(self.pos, self.pos+1, TT::Op(Op::Xor))),
+                '?' if self.peek() == Some('?') => {
+                    tokens.push(Token::new(self.pos, self.pos+2, TT::Op(Op::Coalesce)));
+                    self.step()
+                },
+                '?' if self.peek() == Some('.') => {
+                    tokens.push(Token::new(self.pos, self.pos+2, TT::QDOT));
+                    self.step()
+                },
+                '?' => tokens.push(Token::new(self.pos, self.pos+1, TT::QUESTION)),
+                '(' => {
+                    self.brackets.push(cc);
+                    tokens.push(Token::new(self.pos, self.pos+1, TT::LPR))
+                },
+                ')' => {
+                    self.close_bracket('(');
+                    tokens.push(Token::new(self.pos, self.pos+1, TT::RPR))
+                },
+                '[' => {
+                    self.brackets.push(cc);
+                    tokens.push(Token::new(self.pos, self.pos+1, TT::LSQ))
+                },
+                ']' => {
+                    self.close_bracket('[');
+                    tokens.push(Token::new(self.pos, self.pos+1, TT::RSQ))
+                },
+                '{' => {
+                    self.brackets.push(cc);
+                    tokens.push(Token::new(self.pos, self.pos+1, TT::LBR))
+                },
+                '}' => {
+                    self.close_bracket('{');
+                    tokens.push(Token::new(self.pos, self.pos+1, TT::RBR))
+                },
+                ',' => tokens.push(Token::new(self.pos, self.pos+1, TT::COMMA)),
+                ':' => tokens.push(Token::new(self.pos, self.pos+1, TT::COLON)),
+                '.' if self.peek() == Some('.') => {
+                    let start = self.pos;
+                    self.step();
+                    self.step();
+
+                    let tt = match self.cc {
+                        Some('=') => {
+                            self.step();
+                            TT::DOTDOTEQ
+                        },
+                        _ => TT::DOTDOT
+                    };
+                    tokens.push(Token::new(start, self.pos, tt));
+                    continue
+                },
+                '.' if matches!(self.peek(), Some('0'..='9')) => return Err(ErrorCode::MalformedNumber.error(
+                    "a number can\'t start with a dot, write a 0 before it"
+                )),
+                '.' => tokens.push(Token::new(self.pos, self.pos+1, TT::DOT)),
+                '\n' => tokens.push(Token::new(self.pos, self.pos+1, TT::NL)),
+                ';' => tokens.push(Token::new(self.pos, self.pos+1, TT::SEMI)),
                 '=' => {
-                    let start = self.pos.clone();
+                    let start = self.pos;
                     let eq = self.read_eq();
                     tokens.push(Token::new(start, self.pos, eq));
                     continue;
                 },
                 '|' => {
-                    let start = self.pos.clone();
+                    let start = self.pos;
                     let or = self.read_or()?;
                     tokens.push(Token::new(start, self.pos, or));
                     continue
                 },
                 '&' => {
-                    let start = self.pos.clone();
+                    let start = self.pos;
                     let and = self.read_and()?;
                     tokens.push(Token::new(start, self.pos, and));
                     continue
                 },
                 '<' => {
-                    let start = self.pos.clone();
+                    let start = self.pos;
                     let lt = self.read_lt();
                     tokens.push(Token::new(start, self.pos, lt));
                     continue
                 },
                 '>' => {
-                    let start = self.pos.clone();
+                    let start = self.pos;
                     let gt = self.read_gt();
                     tokens.push(Token::new(start, self.pos, gt));
                     continue
                 },
                 '!' => {
-                    let start = self.pos.clone();
+                    let start = self.pos;
                     let not = self.read_not();
                     tokens.push(Token::new(start, self.pos, not));
                     continue
                 }
-                _ => return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput, format!("invalid character \'{}\'", cc)
-                ))
+                _ => return Err(ErrorCode::InvalidCharacter.error(format!("invalid character \'{}\'", cc)))
             }
             
             self.step()
@@ -258,12 +566,33 @@ impl Lexer {
     }
 }
 
-pub fn get_line_by_char_index<'c>(s: &'c [char], index: usize) -> Option<&'c [char]> {
-    let start = s[..index].into_iter().enumerate().rfind(|(_ , c)| c == &&'\n').map_or(0, |(i, _)| i + 1);
-    let end = s[start..].into_iter().enumerate().find(|(_, c)| c == &&'\n').map_or(s.len(), |(i, _)| start + i);
+pub fn get_line_by_char_index(s: &[char], index: usize) -> Option<&[char]> {
+    let start = s[..index].iter().enumerate().rfind(|(_ , c)| c == &&'\n').map_or(0, |(i, _)| i + 1);
+    let end = s[start..].iter().enumerate().find(|(_, c)| c == &&'\n').map_or(s.len(), |(i, _)| start + i);
     if end > start {
         Some(&s[start..end])
     } else {
         None
     }
+}
+
+pub fn get_position_by_char_index(s: &[char], index: usize) -> (usize, usize) {
+    let index = index.min(s.len());
+    let line = s[..index].iter().filter(|c| c == &&'\n').count();
+    let col = s[..index].iter().rposition(|c| c == &'\n').map_or(index, |i| index - i - 1);
+    (line, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lexer;
+
+    #[test]
+    fn radix_literals_have_no_fractional_part() {
+        for (text, name) in [("0x1.5", "hex"), ("0o7.0", "octal"), ("0b1.1", "binary")] {
+            let err = Lexer::new(text).tokenize().err().map(|err| err.to_string()).unwrap_or_default();
+            assert!(err.contains(&format!("column - 4: [K0002] {} literals can\'t have a fractional part", name)), "{}: {}", text, err);
+        }
+        assert!(Lexer::new("0x1.len()").tokenize().is_ok());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,482 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::highlight;
+
+/// Entries of `~/.kumi_history` kept, older ones are dropped when it's read.
+const HISTORY_LIMIT: usize = 1000;
+
+/// Reads lines from a terminal with cursor movement, history, reverse search and completion.
+///
+/// When stdin isn't a terminal, or it can't be switched to raw mode, lines are read as they come.
+pub struct LineEditor {
+    history: Vec<String>,
+    path: Option<PathBuf>,
+    colors: bool,
+    /// Input entered before the line being edited, it's highlighted as a continuation of it.
+    before: String,
+    /// Terminal columns, long input wraps onto the next rows.
+    width: usize,
+    /// Row of the cursor counted from the prompt's row.
+    row: usize
+}
+
+/// A key press decoded from the bytes the terminal sends for it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    /// Ctrl with a lowercase letter.
+    Ctrl(char),
+    Enter,
+    Backspace,
+    Delete,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    WordLeft,
+    WordRight,
+    Unknown
+}
+
+/// The line being edited.
+struct Line {
+    chars: Vec<char>,
+    cursor: usize
+}
+
+impl Line {
+    fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len()
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Start of the word ending at the cursor.
+    fn word_start(&self) -> usize {
+        let mut start = self.cursor;
+        while start > 0 && is_word_char(self.chars[start - 1]) {
+            start -= 1
+        }
+
+        start
+    }
+
+    fn word_left(&mut self) {
+        while self.cursor > 0 && !is_word_char(self.chars[self.cursor - 1]) {
+            self.cursor -= 1
+        }
+        self.cursor = self.word_start()
+    }
+
+    fn word_right(&mut self) {
+        while self.cursor < self.chars.len() && !is_word_char(self.chars[self.cursor]) {
+            self.cursor += 1
+        }
+        while self.cursor < self.chars.len() && is_word_char(self.chars[self.cursor]) {
+            self.cursor += 1
+        }
+    }
+}
+
+impl LineEditor {
+    /// Reads history from `~/.kumi_history`, `colors` highlights the input as it's typed.
+    pub fn new(colors: bool) -> Self {
+        let path = env::var_os("HOME").map(|home| PathBuf::from(home).join(".kumi_history"));
+        let history = path.as_ref().map_or(Vec::new(), load_history);
+
+        Self { history, path, colors, before: String::new(), width: 80, row: 0 }
+    }
+
+    /// The next line without its new line, `None` at the end of input.
+    ///
+    /// `before` is the input the line continues. Ctrl-C gives an [`io::ErrorKind::Interrupted`] error and drops
+    /// what was typed.
+    pub fn read_line(&mut self, prompt: &str, before: &str, completions: &[String]) -> io::Result<Option<String>> {
+        if !io::stdin().is_terminal() {
+            return read_plain(prompt);
+        }
+        let raw = match RawMode::enable() {
+            Ok(raw) => raw,
+            Err(_) => return read_plain(prompt),
+        };
+
+        self.width = terminal_width().unwrap_or(80);
+        self.row = 0;
+        self.before = before.to_string();
+        let result = self.edit(prompt, completions);
+        drop(raw);
+
+        if let Ok(Some(line)) = &result {
+            self.add_history(line)
+        }
+
+        result
+    }
+
+    fn edit(&mut self, prompt: &str, completions: &[String]) -> io::Result<Option<String>> {
+        let mut line = Line { chars: Vec::new(), cursor: 0 };
+        // the history entry shown, `history.len()` is the line being typed
+        let mut position = self.history.len();
+        let mut draft = String::new();
+
+        self.refresh(prompt, &line)?;
+        let mut pending = None;
+        loop {
+            let key = match pending.take() {
+                Some(key) => key,
+                None => read_key()?,
+            };
+
+            match key {
+                Key::Enter => {
+                    line.cursor = line.chars.len();
+                    self.refresh(prompt, &line)?;
+                    self.write("\n")?;
+                    return Ok(Some(line.text()));
+                },
+                Key::Ctrl('c') => {
+                    line.cursor = line.chars.len();
+                    self.refresh(prompt, &line)?;
+                    self.write("^C\n")?;
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "input cancelled"));
+                },
+                Key::Ctrl('d') if line.chars.is_empty() => {
+                    self.write("\n")?;
+                    return Ok(None);
+                },
+                Key::Ctrl('d') | Key::Delete => if line.cursor < line.chars.len() {
+                    line.chars.remove(line.cursor);
+                },
+                Key::Backspace | Key::Ctrl('h') => if line.cursor > 0 {
+                    line.cursor -= 1;
+                    line.chars.remove(line.cursor);
+                },
+                Key::Left | Key::Ctrl('b') => line.cursor = line.cursor.saturating_sub(1),
+                Key::Right | Key::Ctrl('f') => line.cursor = (line.cursor + 1).min(line.chars.len()),
+                Key::Home | Key::Ctrl('a') => line.cursor = 0,
+                Key::End | Key::Ctrl('e') => line.cursor = line.chars.len(),
+                Key::WordLeft => line.word_left(),
+                Key::WordRight => line.word_right(),
+                Key::Ctrl('k') => line.chars.truncate(line.cursor),
+                Key::Ctrl('u') => {
+                    line.chars.drain(..line.cursor);
+                    line.cursor = 0
+                },
+                Key::Ctrl('w') => {
+                    let end = line.cursor;
+                    line.word_left();
+                    line.chars.drain(line.cursor..end);
+                },
+                Key::Ctrl('l') => {
+                    self.write("\x1b[H\x1b[2J")?;
+                    self.row = 0
+                },
+                Key::Up | Key::Ctrl('p') => if position > 0 {
+                    if position == self.history.len() {
+                        draft = line.text()
+                    }
+                    position -= 1;
+                    line.set(&self.history[position])
+                },
+                Key::Down | Key::Ctrl('n') => if position < self.history.len() {
+                    position += 1;
+                    line.set(self.history.get(position).unwrap_or(&draft))
+                },
+                Key::Ctrl('r') => pending = self.search(&mut line)?,
+                Key::Tab => self.complete(prompt, &mut line, completions)?,
+                Key::Char(c) => {
+                    line.chars.insert(line.cursor, c);
+                    line.cursor += 1
+                },
+                Key::Ctrl(_) | Key::Unknown => {},
+            }
+
+            self.refresh(prompt, &line)?;
+        }
+    }
+
+    /// Reverse search through the history, the key that ends it is given back to be handled as usual.
+    ///
+    /// Typing narrows the search, Ctrl-R finds an older match, Ctrl-G and Ctrl-C go back to the line as it was.
+    fn search(&mut self, line: &mut Line) -> io::Result<Option<Key>> {
+        let mut query = String::new();
+        let mut found: Option<(usize, usize)> = None;
+        let original = line.text();
+
+        loop {
+            match found {
+                Some((index, pos)) => {
+                    line.set(&self.history[index]);
+                    line.cursor = pos
+                },
+                None => line.set(&original),
+            }
+            let prompt = format!("({}reverse-i-search)`{}\': ", if found.is_none() && !query.is_empty() { "failed " } else { "" }, query);
+            self.refresh(&prompt, line)?;
+
+            match read_key()? {
+                Key::Char(c) => {
+                    query.push(c);
+                    found = self.find(&query, found.map_or(self.history.len(), |(index, _)| index + 1))
+                },
+                Key::Backspace => {
+                    query.pop();
+                    found = if query.is_empty() { None } else { self.find(&query, self.history.len()) }
+                },
+                Key::Ctrl('r') => if let Some((index, _)) = found {
+                    found = self.find(&query, index).or(found)
+                },
+                Key::Ctrl('g') | Key::Ctrl('c') => {
+                    line.set(&original);
+                    return Ok(None);
+                },
+                key => return Ok(Some(key)),
+            }
+        }
+    }
+
+    /// The newest history entry before `before` containing `query`, with the char index of the match.
+    fn find(&self, query: &str, before: usize) -> Option<(usize, usize)> {
+        self.history[..before].iter().enumerate().rev().find_map(|(index, entry)| {
+            entry.find(query).map(|byte| (index, entry[..byte].chars().count()))
+        })
+    }
+
+    /// Completes the word before the cursor, several candidates are completed as far as they agree and listed.
+    fn complete(&mut self, prompt: &str, line: &mut Line, completions: &[String]) -> io::Result<()> {
+        let start = line.word_start();
+        let prefix: String = line.chars[start..line.cursor].iter().collect();
+        if prefix.is_empty() {
+            return Ok(());
+        }
+
+        let mut candidates: Vec<&String> = completions.iter().filter(|word| word.starts_with(&prefix)).collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let common = match candidates.first() {
+            Some(first) => candidates.iter().fold(first.as_str(), |common, word| {
+                let len = common.chars().zip(word.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a.len_utf8()).sum();
+                &common[..len]
+            }),
+            None => return self.write("\x07"),
+        };
+
+        if common.len() > prefix.len() || candidates.len() == 1 {
+            for c in common[prefix.len()..].chars() {
+                line.chars.insert(line.cursor, c);
+                line.cursor += 1
+            }
+            return Ok(());
+        }
+
+        // list the candidates under the input and draw it again below them
+        let cursor = line.cursor;
+        line.cursor = line.chars.len();
+        self.refresh(prompt, line)?;
+        let names: Vec<&str> = candidates.iter().map(|word| word.as_str()).collect();
+        self.write(&format!("\n{}\n", names.join("  ")))?;
+        self.row = 0;
+        line.cursor = cursor;
+
+        Ok(())
+    }
+
+    /// Draws the prompt and the line again and puts the cursor where it belongs.
+    fn refresh(&mut self, prompt: &str, line: &Line) -> io::Result<()> {
+        let mut out = String::new();
+        if self.row > 0 {
+            out += &format!("\x1b[{}A", self.row);
+        }
+        out += "\r\x1b[J";
+        out += prompt;
+
+        let text = line.text();
+        out += &if self.colors { highlight::colorize_after(&self.before, &text) } else { text };
+
+        let prompt_len = prompt.chars().count();
+        let end = prompt_len + line.chars.len();
+        let cursor = prompt_len + line.cursor;
+        if end > 0 && end.is_multiple_of(self.width) {
+            // the terminal waits at the end of a full row before wrapping
+            out += "\n";
+        }
+
+        let (end_row, cursor_row) = (end / self.width, cursor / self.width);
+        if end_row > cursor_row {
+            out += &format!("\x1b[{}A", end_row - cursor_row);
+        }
+        out += "\r";
+        if !cursor.is_multiple_of(self.width) {
+            out += &format!("\x1b[{}C", cursor % self.width);
+        }
+        self.row = cursor_row;
+
+        self.write(&out)
+    }
+
+    fn write(&self, text: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()
+    }
+
+    /// Remembers `line` and appends it to the history file, blank lines and repeats are skipped.
+    pub fn add_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.history.push(line.to_string());
+
+        if let Some(path) = &self.path {
+            // failing to save history shouldn't interrupt the session
+            let _ = OpenOptions::new().create(true).append(true).open(path)
+                .and_then(|mut file| writeln!(file, "{}", line));
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// The last [`HISTORY_LIMIT`] entries of the history file, which is cut down to them when it grew longer.
+fn load_history(path: &PathBuf) -> Vec<String> {
+    let text = fs::read_to_string(path).unwrap_or_default();
+    let mut history: Vec<String> = text.lines().filter(|line| !line.trim().is_empty()).map(String::from).collect();
+
+    if history.len() > HISTORY_LIMIT {
+        history.drain(..history.len() - HISTORY_LIMIT);
+        let _ = fs::write(path, history.iter().map(|line| format!("{}\n", line)).collect::<String>());
+    }
+
+    history
+}
+
+fn read_plain(prompt: &str) -> io::Result<Option<String>> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+fn read_byte() -> io::Result<u8> {
+    let mut byte = [0];
+    io::stdin().lock().read_exact(&mut byte)?;
+
+    Ok(byte[0])
+}
+
+fn read_key() -> io::Result<Key> {
+    let key = match read_byte()? {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f => Key::Backspace,
+        0x1b => escape()?,
+        byte @ 1..=26 => Key::Ctrl((b'a' + byte - 1) as char),
+        byte if byte < 0x80 => Key::Char(byte as char),
+        byte => {
+            // the rest of a UTF-8 sequence, its length is given by the leading ones of the first byte
+            let mut bytes = vec![byte];
+            for _ in 1..byte.leading_ones() {
+                bytes.push(read_byte()?)
+            }
+            String::from_utf8(bytes).ok().and_then(|text| text.chars().next()).map_or(Key::Unknown, Key::Char)
+        },
+    };
+
+    Ok(key)
+}
+
+/// Decodes what follows an escape byte, arrows and the other keys terminals send as escape sequences.
+fn escape() -> io::Result<Key> {
+    let key = match read_byte()? {
+        b'[' => {
+            let mut params = String::new();
+            let last = loop {
+                match read_byte()? {
+                    byte @ 0x40..=0x7e => break byte,
+                    byte => params.push(byte as char),
+                }
+            };
+
+            match (params.as_str(), last) {
+                ("", b'A') => Key::Up,
+                ("", b'B') => Key::Down,
+                ("", b'C') => Key::Right,
+                ("", b'D') => Key::Left,
+                ("", b'H') | ("1" | "7", b'~') => Key::Home,
+                ("", b'F') | ("4" | "8", b'~') => Key::End,
+                ("3", b'~') => Key::Delete,
+                ("1;3" | "1;5", b'C') => Key::WordRight,
+                ("1;3" | "1;5", b'D') => Key::WordLeft,
+                _ => Key::Unknown
+            }
+        },
+        b'O' => match read_byte()? {
+            b'A' => Key::Up,
+            b'B' => Key::Down,
+            b'C' => Key::Right,
+            b'D' => Key::Left,
+            b'H' => Key::Home,
+            b'F' => Key::End,
+            _ => Key::Unknown
+        },
+        b'b' => Key::WordLeft,
+        b'f' => Key::WordRight,
+        _ => Key::Unknown
+    };
+
+    Ok(key)
+}
+
+/// Columns of the terminal on stdin.
+fn terminal_width() -> Option<usize> {
+    let size = stty(&["size"]).ok()?;
+    size.split_whitespace().nth(1)?.parse().ok().filter(|width| *width > 0)
+}
+
+/// Keeps the terminal reading key by key without echoing or signals, the old settings come back on drop.
+struct RawMode {
+    saved: String
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?.trim().to_string();
+        stty(&["-icanon", "-echo", "-isig", "-ixon", "-icrnl", "min", "1"])?;
+
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Runs `stty` on the terminal stdin is, it's what changes the terminal's settings without any dependencies.
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!("stty {} failed", args.join(" "))));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
\ No newline at end of file
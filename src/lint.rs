@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::mem;
+
+use crate::lexer::{get_line_by_char_index, get_position_by_char_index};
+use crate::node::Node;
+use crate::op::Op;
+use crate::token::Token;
+use crate::types::Type;
+
+/// Warnings reported by the [`Linter`] before evaluation.
+///
+/// Like error codes, lint codes are stable and only ever appended.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    UnusedVariable,
+    ShadowedVariable,
+    UnreachableCode,
+    ConstantCondition
+}
+
+impl Lint {
+    fn lints() -> Vec<Self> {
+        vec![
+            Lint::UnusedVariable,
+            Lint::ShadowedVariable,
+            Lint::UnreachableCode,
+            Lint::ConstantCondition
+        ]
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_uppercase();
+        Self::lints().into_iter().find(|lint| lint.code() == code)
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "W0001",
+            Lint::ShadowedVariable => "W0002",
+            Lint::UnreachableCode => "W0003",
+            Lint::ConstantCondition => "W0004",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused variable",
+            Lint::ShadowedVariable => "variable redeclared in the same scope",
+            Lint::UnreachableCode => "unreachable code",
+            Lint::ConstantCondition => "condition is always the same",
+        }
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => r#"A variable declared with `let`, a `match` binding or a `catch` name is
+never read.
+
+Example:
+
+    let width = 4
+    let height = 2
+    height * 2
+
+`width` is computed but never used. Remove the declaration, or prefix the
+name with `_` if the value is kept on purpose. Bindings and caught errors
+that aren't needed can be written as `ok(_)` and `catch _`."#,
+            Lint::ShadowedVariable => r#"A `let` declares a name that already exists in the same scope, the
+previous value becomes unreachable.
+
+Example:
+
+    let total = 10
+    let total = 20
+
+Pick a different name if both values are needed, or drop the first
+declaration."#,
+            Lint::UnreachableCode => r#"A statement follows a `return`, `throw`, `break` or `continue` in the same
+block, so it can never run.
+
+Example:
+
+    fn half(x) {
+        return x / 2
+        x / 3
+    }
+
+Remove the statements after `return`, `throw`, `break` or `continue`, or
+move them before it."#,
+            Lint::ConstantCondition => r#"The condition of an `if` or of `cond ? a : b` is built only from literals,
+like `true`, `1 > 2` or `!false && true`, so one of the branches never runs.
+An operand of `&&` or `||` that is always the same is reported as well when
+it doesn't decide the condition, as in `true && x`.
+
+Example:
+
+    let limit = if 1 > 2 { 10 } else { 20 }
+
+Keep only the branch that runs, or replace the literals with the check that
+was meant."#,
+        }
+    }
+}
+
+impl ToString for Lint {
+    fn to_string(&self) -> String {
+        format!("{}: {}", self.code(), self.title())
+    }
+}
+
+pub struct Warning {
+    lint: Lint,
+    token: Token,
+    message: String
+}
+
+impl Warning {
+    pub fn new(lint: Lint, token: Token, message: String) -> Self {
+        Self { lint, token, message }
+    }
+
+    pub fn get_lint(&self) -> Lint {
+        self.lint
+    }
+
+    pub fn get_token(&self) -> &Token {
+        &self.token
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn format(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let start = *self.token.get_start();
+        let end = *self.token.get_end();
+
+        let line = get_line_by_char_index(&chars, start).unwrap_or(&[]);
+        let (line_number, col) = get_position_by_char_index(&chars, start);
+        let pointers = " ".repeat(col) + &"^".repeat(end.saturating_sub(start).max(1));
+
+        format!(
+            "...\n{}\n{}\nWarning on line - {}, column - {}: [{}] {}\n...",
+            line.iter().collect::<String>(),
+            pointers,
+            line_number+1,
+            col+1,
+            self.lint.code(),
+            self.message
+        )
+    }
+}
+
+struct Binding {
+    token: Token,
+    used: bool,
+    reported: bool
+}
+
+/// Static checks over a parsed [`Node`] tree, run before it's evaluated.
+pub struct Linter {
+    scopes: Vec<HashMap<String, Binding>>,
+    warnings: Vec<Warning>,
+    loops: usize,
+    unused_globals: bool
+}
+
+impl Linter {
+    /// `unused_globals` controls whether never-read top-level bindings are reported,
+    /// the REPL disables it since a later input may still read them.
+    pub fn new(unused_globals: bool) -> Self {
+        Self { scopes: Vec::new(), warnings: Vec::new(), loops: 0, unused_globals }
+    }
+
+    pub fn lint(mut self, node: &Node) -> Vec<Warning> {
+        self.scopes.push(HashMap::new());
+        self.visit(node);
+        self.leave_scope(self.unused_globals);
+
+        self.warnings.sort_by_key(|warning| *warning.token.get_start());
+        self.warnings
+    }
+
+    fn visit(&mut self, node: &Node) {
+        match node {
+            Node::Number(_, _) => {},
+            Node::GetVar(name, _) => self.read(name),
+            Node::NotOp(node, _) | Node::UnaryOp(node, _) | Node::BitNotOp(node, _) | Node::IsNone(node) |
+            Node::GetMember(node, _, _, _) | Node::Unwrap(node, _) | Node::Throw(node, _) => self.visit(node),
+            Node::BinOp(left, _, right, _) | Node::LogicOp(left, _, right, _) | Node::Index(left, right, _) => {
+                self.visit(left);
+                self.visit(right)
+            },
+            Node::DeclareVar(name, value, token) => {
+                self.visit(value);
+                self.declare(name, token, true)
+            },
+            Node::DeclareFn(name, params, body, _, token) => {
+                self.declare(name, token, false);
+
+                let loops = mem::replace(&mut self.loops, 0);
+                self.scopes.push(HashMap::new());
+                for param in params {
+                    self.declare(param, token, false)
+                }
+                self.visit(body);
+                self.leave_scope(true);
+                self.loops = loops
+            },
+            Node::Range(start, end, step, _, _) => {
+                self.visit(start);
+                self.visit(end);
+                if let Some(step) = step {
+                    self.visit(step)
+                }
+            },
+            Node::List(items, _) => {
+                for item in items {
+                    self.visit(item)
+                }
+            },
+            Node::Call(callee, args, _) | Node::CallMethod(callee, _, args, _, _) => {
+                self.visit(callee);
+                for arg in args {
+                    self.visit(arg)
+                }
+            },
+            Node::Return(value, _) => {
+                if let Some(value) = value {
+                    self.visit(value)
+                }
+            },
+            Node::Try(body, catches, finally, _) => {
+                self.visit(body);
+
+                for catch in catches {
+                    self.scopes.push(HashMap::new());
+                    self.declare(catch.get_name(), catch.get_token(), true);
+                    self.visit(catch.get_body());
+                    self.leave_scope(true)
+                }
+
+                if let Some(finally) = finally {
+                    self.visit(finally)
+                }
+            },
+            Node::Match(node, arms, _) => {
+                self.visit(node);
+
+                for arm in arms {
+                    self.scopes.push(HashMap::new());
+                    for (name, token) in arm.get_pattern().get_names() {
+                        self.declare(name, token, true)
+                    }
+                    self.visit(arm.get_body());
+                    self.leave_scope(true)
+                }
+            },
+            Node::If(condition, then, otherwise, _) => {
+                self.constant_condition(condition);
+                self.visit(condition);
+                self.visit(then);
+                if let Some(otherwise) = otherwise {
+                    self.visit(otherwise)
+                }
+            },
+            // like the interpreter, a loop shares the scope around it
+            Node::For(name, iterable, body, token) => {
+                self.visit(iterable);
+
+                self.loops += 1;
+                self.declare(name, token, true);
+                self.visit(body);
+                self.loops -= 1
+            },
+            Node::Break(_) | Node::Continue(_) => {},
+            Node::Statements(statements) => self.visit_statements(statements)
+        }
+    }
+
+    fn visit_statements(&mut self, statements: &[Node]) {
+        let mut left: Option<&str> = None;
+
+        for statement in statements {
+            if let Some(keyword) = left.take() {
+                if let Some(token) = statement.first_token() {
+                    self.warnings.push(Warning::new(
+                        Lint::UnreachableCode,
+                        token.clone(),
+                        format!("this statement comes after `{}` and never runs", keyword)
+                    ));
+                }
+            }
+
+            self.visit(statement);
+
+            left = match statement {
+                Node::Return(_, _) => Some("return"),
+                Node::Throw(_, _) => Some("throw"),
+                Node::Break(_) => Some("break"),
+                Node::Continue(_) => Some("continue"),
+                _ => None
+            }
+        }
+    }
+
+    /// Reports a condition that is the same whatever the variables hold, or else the operands of its
+    /// `&&` and `||` that never change it.
+    fn constant_condition(&mut self, condition: &Node) {
+        if let (Some(Type::Bool(value)), Some(token)) = (fold(condition), condition.first_token()) {
+            return self.warnings.push(Warning::new(
+                Lint::ConstantCondition,
+                token.clone(),
+                format!("this condition is always `{}`, the {} branch never runs", value, if value { "else" } else { "first" })
+            ));
+        }
+
+        match condition {
+            Node::NotOp(node, _) => self.constant_condition(node),
+            Node::LogicOp(left, op @ (Op::And | Op::Or), right, _) => for operand in [left, right] {
+                match (fold(operand), operand.first_token()) {
+                    (Some(Type::Bool(value)), Some(token)) => self.warnings.push(Warning::new(
+                        Lint::ConstantCondition,
+                        token.clone(),
+                        format!("this operand of `{}` is always `{}`, the condition is the same without it", op.to_string(), value)
+                    )),
+                    _ => self.constant_condition(operand),
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn read(&mut self, name: &str) {
+        if let Some(binding) = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name)) {
+            binding.used = true
+        }
+    }
+
+    /// `reported` decides whether the binding may be reported as unused, functions and parameters never are.
+    fn declare(&mut self, name: &str, token: &Token, reported: bool) {
+        let scope = match self.scopes.last_mut() {
+            Some(scope) => scope,
+            None => return,
+        };
+
+        let previous = scope.insert(name.to_string(), Binding { token: token.clone(), used: false, reported });
+        if let Some(previous) = previous {
+            // inside a loop the next iteration may still read the previous value
+            if self.loops > 0 {
+                return self.unused(name, previous);
+            }
+
+            self.warnings.push(Warning::new(
+                Lint::ShadowedVariable,
+                token.clone(),
+                format!("`{}` is already declared in this scope, the previous value becomes unreachable", name)
+            ));
+            self.unused(name, previous);
+        }
+    }
+
+    fn leave_scope(&mut self, report_unused: bool) {
+        let scope = match self.scopes.pop() {
+            Some(scope) => scope,
+            None => return,
+        };
+
+        if !report_unused {
+            return
+        }
+
+        for (name, binding) in scope {
+            self.unused(&name, binding)
+        }
+    }
+
+    fn unused(&mut self, name: &str, binding: Binding) {
+        if binding.used || !binding.reported || name.starts_with('_') {
+            return
+        }
+
+        self.warnings.push(Warning::new(
+            Lint::UnusedVariable,
+            binding.token,
+            format!("`{}` is declared but never read", name)
+        ));
+    }
+}
+
+/// The value of `node` when it's the same on every run: literals combined by operators, with `false && x`
+/// and `true || x` decided by their constant side. `None` for anything that reads a variable, calls or fails.
+fn fold(node: &Node) -> Option<Type> {
+    match node {
+        Node::Number(value, _) => Some(value.clone()),
+        Node::NotOp(node, _) => match fold(node)? {
+            Type::Bool(value) => Some(Type::Bool(!value)),
+            _ => None
+        },
+        Node::UnaryOp(node, _) => fold(node)?.inverted().ok(),
+        Node::BitNotOp(node, _) => fold(node)?.bit_not().ok(),
+        Node::BinOp(left, op, right, _) => op.execute(&fold(left)?, &fold(right)?).ok(),
+        Node::IsNone(node) => fold(node).map(|value| Type::Bool(matches!(value, Type::None))),
+        Node::LogicOp(left, Op::Coalesce, right, _) => match fold(left)? {
+            Type::None => fold(right),
+            value => Some(value),
+        },
+        Node::LogicOp(left, op, right, _) => {
+            // `false` decides an `&&` and `true` an `||` whatever the other operand is
+            let decides = matches!(op, Op::Or);
+            match (fold(left), fold(right)) {
+                (Some(Type::Bool(a)), _) if a == decides => Some(Type::Bool(decides)),
+                (_, Some(Type::Bool(b))) if b == decides => Some(Type::Bool(decides)),
+                (Some(Type::Bool(_)), Some(Type::Bool(b))) => Some(Type::Bool(b)),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Linter, Warning};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// The warnings with `code` for `text`.
+    fn warnings(text: &str, code: &str) -> Vec<Warning> {
+        let tokens = Lexer::new(text).tokenize().expect("the test program lexes");
+        let node = Parser::new(&tokens).parse().map_err(|err| err.format(text)).expect("the test program parses");
+
+        Linter::new(false).lint(&node).into_iter().filter(|warning| warning.get_lint().code() == code).collect()
+    }
+
+    /// The messages of the W0004 warnings for `text`.
+    fn constant_conditions(text: &str) -> Vec<String> {
+        warnings(text, "W0004").into_iter()
+            .map(|warning| warning.get_message().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn folds_conditions_made_of_literals() {
+        for condition in ["1 > 2", "!true", "false && x", "x && false", "2 + 2 != 4", "none ?? false"] {
+            assert_eq!(
+                constant_conditions(&format!("let x = true\nif {} {{ 1 }} else {{ 2 }}", condition)),
+                ["this condition is always `false`, the first branch never runs"],
+                "{}", condition
+            );
+        }
+        assert_eq!(constant_conditions("let x = true\nx || true ? 1 : 2"), ["this condition is always `true`, the else branch never runs"]);
+    }
+
+    #[test]
+    fn reports_operands_that_never_change_the_condition() {
+        assert_eq!(
+            constant_conditions("let x = true\nif true && x { 1 }"),
+            ["this operand of `&&` is always `true`, the condition is the same without it"]
+        );
+        assert_eq!(
+            constant_conditions("let x = true\nif !(x || 1 < 0) { 1 }"),
+            ["this operand of `||` is always `false`, the condition is the same without it"]
+        );
+    }
+
+    #[test]
+    fn keeps_quiet_about_conditions_that_vary() {
+        assert!(constant_conditions("let x = 3\nif x > 2 && x < 5 { 1 }").is_empty());
+        assert!(constant_conditions("if 1 / 0 > 1 { 1 }").is_empty());
+    }
+
+    #[test]
+    fn unused_catch_names_point_at_the_name() {
+        for text in ["try { 1 } catch err { 2 }", "try { 1 } catch A | B err { 2 }"] {
+            let warnings = warnings(text, "W0001");
+            let starts: Vec<usize> = warnings.iter().map(|warning| *warning.get_token().get_start()).collect();
+
+            assert_eq!(starts, [text.find("err").unwrap()], "{}", text);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::analysis::{Analysis, SymbolKind};
+use crate::formatter::Formatter;
+use crate::highlight::{classify, TokenClass};
+use crate::json::Json;
+use crate::keyword::Keyword;
+use crate::token::TT;
+
+// JSON-RPC error codes
+const PARSE_ERROR: f64 = -32700.0;
+const INVALID_REQUEST: f64 = -32600.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+
+// LSP enums
+const SYNC_FULL: usize = 1;
+const SEVERITY_ERROR: usize = 1;
+const SEVERITY_WARNING: usize = 2;
+const COMPLETION_FUNCTION: usize = 3;
+const COMPLETION_VARIABLE: usize = 6;
+const COMPLETION_KEYWORD: usize = 14;
+const SYMBOL_FUNCTION: usize = 12;
+const SYMBOL_VARIABLE: usize = 13;
+
+struct Document {
+    text: String,
+    chars: Vec<char>,
+    analysis: Analysis,
+    /// The last version that parsed while the current one doesn't, completion still offers its names.
+    fallback: Option<Analysis>
+}
+
+impl Document {
+    fn new(text: String) -> Self {
+        Self { chars: text.chars().collect(), analysis: Analysis::new(&text), text, fallback: None }
+    }
+
+    fn update(&mut self, text: String) {
+        let previous = std::mem::replace(self, Document::new(text));
+        if !self.analysis.is_parsed() {
+            self.fallback = if previous.analysis.is_parsed() { Some(previous.analysis) } else { previous.fallback };
+        }
+    }
+
+    /// `{"line": .., "character": ..}` of the char at `index`, characters count UTF-16 code units.
+    fn position(&self, index: usize) -> Json {
+        let index = index.min(self.chars.len());
+        let line_start = self.chars[..index].iter().rposition(|c| c == &'\n').map_or(0, |i| i + 1);
+        let line = self.chars[..line_start].iter().filter(|c| c == &&'\n').count();
+        let character: usize = self.chars[line_start..index].iter().map(|c| c.len_utf16()).sum();
+
+        Json::object(vec![("line", line.into()), ("character", character.into())])
+    }
+
+    fn range(&self, start: usize, end: usize) -> Json {
+        Json::object(vec![("start", self.position(start)), ("end", self.position(end))])
+    }
+
+    /// Index of the char at an LSP position, positions past the end of a line stop at its end.
+    fn offset(&self, position: &Json) -> usize {
+        let line = position.get("line").and_then(|line| line.as_usize()).unwrap_or(0);
+        let character = position.get("character").and_then(|character| character.as_usize()).unwrap_or(0);
+
+        let mut index = 0;
+        for _ in 0..line {
+            match self.chars[index..].iter().position(|c| c == &'\n') {
+                Some(newline) => index += newline + 1,
+                None => return self.chars.len(),
+            }
+        }
+
+        let mut units = 0;
+        while index < self.chars.len() && self.chars[index] != '\n' && units < character {
+            units += self.chars[index].len_utf16();
+            index += 1
+        }
+
+        index
+    }
+}
+
+/// `kumi lsp`, a Language Server Protocol server reading requests from stdin and answering on stdout.
+pub struct Server {
+    documents: HashMap<String, Document>,
+    shutdown: bool
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self { documents: HashMap::new(), shutdown: false }
+    }
+
+    /// Serves until the client sends `exit`, returns the status the process should exit with.
+    pub fn run(&mut self) -> io::Result<i32> {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+
+        loop {
+            let body = match read_message(&mut input)? {
+                Some(body) => body,
+                None => return Ok(1),
+            };
+
+            let message = match Json::parse(&body) {
+                Ok(message) => message,
+                Err(err) => {
+                    send(&error_response(Json::Null, PARSE_ERROR, &err.to_string()))?;
+                    continue
+                },
+            };
+
+            let method = message.get("method").and_then(|method| method.as_str()).unwrap_or("");
+            let params = message.get("params").cloned().unwrap_or(Json::Null);
+
+            match message.get("id").cloned() {
+                Some(id) => {
+                    let response = match self.request(method, &params) {
+                        Ok(result) => Json::object(vec![("jsonrpc", "2.0".into()), ("id", id), ("result", result)]),
+                        Err((code, err)) => error_response(id, code, &err),
+                    };
+                    send(&response)?
+                },
+                None if method == "exit" => return Ok(if self.shutdown { 0 } else { 1 }),
+                None => self.notification(method, &params)?,
+            }
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, (f64, String)> {
+        if self.shutdown && method != "shutdown" {
+            return Err((INVALID_REQUEST, "the server is shutting down".to_string()));
+        }
+
+        let document = || {
+            let uri = params.get("textDocument").and_then(|document| document.get("uri")).and_then(|uri| uri.as_str());
+            uri.and_then(|uri| self.documents.get(uri).map(|document| (uri, document)))
+        };
+        let position = |document: &Document| params.get("position").map_or(0, |position| document.offset(position));
+
+        let result = match method {
+            "initialize" => capabilities(),
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            },
+            "textDocument/hover" => document().map_or(Json::Null, |(_, document)| hover(document, position(document))),
+            "textDocument/definition" => document().map_or(Json::Null, |(uri, document)| definition(uri, document, position(document))),
+            "textDocument/documentSymbol" => document().map_or(Json::Null, |(_, document)| document_symbols(document, None)),
+            "textDocument/completion" => document().map_or(Json::Null, |(_, document)| completion(document, position(document))),
+            "textDocument/formatting" => document().map_or(Json::Null, |(_, document)| formatting(document)),
+            "textDocument/semanticTokens/full" => document().map_or(Json::Null, |(_, document)| semantic_tokens(document)),
+            _ => return Err((METHOD_NOT_FOUND, format!("{} isn\'t supported", method))),
+        };
+
+        Ok(result)
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> io::Result<()> {
+        let document = params.get("textDocument");
+        let uri = match document.and_then(|document| document.get("uri")).and_then(|uri| uri.as_str()) {
+            Some(uri) => uri.to_string(),
+            None => return Ok(()),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = document.and_then(|document| document.get("text")).and_then(|text| text.as_str()).unwrap_or("");
+                self.documents.insert(uri.clone(), Document::new(text.to_string()));
+            },
+            "textDocument/didChange" => {
+                // the server asks for full sync, so the last change holds the whole text
+                let changes = params.get("contentChanges").and_then(|changes| changes.as_array()).unwrap_or(&[]);
+                let text = match changes.last().and_then(|change| change.get("text")).and_then(|text| text.as_str()) {
+                    Some(text) => text.to_string(),
+                    None => return Ok(()),
+                };
+
+                match self.documents.get_mut(&uri) {
+                    Some(document) => document.update(text),
+                    None => {
+                        self.documents.insert(uri.clone(), Document::new(text));
+                    },
+                }
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return publish_diagnostics(&uri, Json::Array(Vec::new()));
+            },
+            _ => return Ok(()),
+        }
+
+        match self.documents.get(&uri) {
+            Some(document) => publish_diagnostics(&uri, diagnostics(document)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn capabilities() -> Json {
+    Json::object(vec![
+        ("capabilities", Json::object(vec![
+            ("textDocumentSync", SYNC_FULL.into()),
+            ("hoverProvider", true.into()),
+            ("definitionProvider", true.into()),
+            ("documentSymbolProvider", true.into()),
+            ("completionProvider", Json::object(vec![])),
+            ("documentFormattingProvider", true.into()),
+            ("semanticTokensProvider", Json::object(vec![
+                ("legend", Json::object(vec![
+                    ("tokenTypes", Json::Array(TokenClass::classes().iter().map(|class| class.get_name().into()).collect())),
+                    ("tokenModifiers", Json::Array(Vec::new()))
+                ])),
+                ("full", true.into())
+            ]))
+        ])),
+        ("serverInfo", Json::object(vec![("name", "kumi".into()), ("version", env!("CARGO_PKG_VERSION").into())]))
+    ])
+}
+
+fn diagnostics(document: &Document) -> Json {
+    Json::Array(document.analysis.get_diagnostics().iter().map(|diagnostic| Json::object(vec![
+        ("range", document.range(diagnostic.get_start(), diagnostic.get_end())),
+        ("severity", if diagnostic.is_warning() { SEVERITY_WARNING } else { SEVERITY_ERROR }.into()),
+        ("source", "kumi".into()),
+        ("message", diagnostic.get_message().into())
+    ])).collect())
+}
+
+fn hover(document: &Document, index: usize) -> Json {
+    let analysis = &document.analysis;
+
+    let (token, contents) = match analysis.symbol_at(index) {
+        Some((token, symbol)) => match symbol.get_doc() {
+            Some(doc) => (token, format!("```kumi\n{}\n```\n\n{}", analysis.describe(symbol), doc)),
+            None => (token, format!("```kumi\n{}\n```", analysis.describe(symbol))),
+        },
+        None => match analysis.token_at(index).map(|token| (token, token.get_tt())) {
+            Some((token, TT::Type(value))) => (token, format!("```kumi\n{}\n```", value.get_type_name())),
+            _ => return Json::Null,
+        },
+    };
+
+    Json::object(vec![
+        ("contents", Json::object(vec![("kind", "markdown".into()), ("value", contents.into())])),
+        ("range", document.range(*token.get_start(), *token.get_end()))
+    ])
+}
+
+fn definition(uri: &str, document: &Document, index: usize) -> Json {
+    match document.analysis.symbol_at(index).and_then(|(_, symbol)| symbol.get_token()) {
+        Some(token) => Json::object(vec![
+            ("uri", uri.into()),
+            ("range", document.range(*token.get_start(), *token.get_end()))
+        ]),
+        None => Json::Null,
+    }
+}
+
+/// `let` and `fn` declarations inside the function `parent`, or at the top level for `None`.
+fn document_symbols(document: &Document, parent: Option<usize>) -> Json {
+    let symbols = document.analysis.get_symbols();
+
+    Json::Array(symbols.iter().enumerate().filter_map(|(index, symbol)| {
+        let kind = match symbol.get_kind() {
+            SymbolKind::Function => SYMBOL_FUNCTION,
+            SymbolKind::Variable => SYMBOL_VARIABLE,
+            _ => return None,
+        };
+        let token = symbol.get_token().filter(|_| symbol.get_parent() == parent)?;
+        let range = document.range(*token.get_start(), *token.get_end());
+
+        let mut fields = vec![
+            ("name", symbol.get_name().into()),
+            ("detail", document.analysis.describe(symbol).into()),
+            ("kind", kind.into()),
+            ("range", range.clone()),
+            ("selectionRange", range)
+        ];
+        if kind == SYMBOL_FUNCTION {
+            fields.push(("children", document_symbols(document, Some(index))));
+        }
+
+        Some(Json::object(fields))
+    }).collect())
+}
+
+fn completion(document: &Document, index: usize) -> Json {
+    let analysis = match &document.fallback {
+        Some(fallback) if !document.analysis.is_parsed() => fallback,
+        _ => &document.analysis,
+    };
+
+    let mut items: Vec<Json> = analysis.visible_at(index).into_iter().map(|symbol| {
+        let kind = match symbol.get_kind() {
+            SymbolKind::Function | SymbolKind::Builtin => COMPLETION_FUNCTION,
+            _ => COMPLETION_VARIABLE,
+        };
+
+        Json::object(vec![
+            ("label", symbol.get_name().into()),
+            ("kind", kind.into()),
+            ("detail", analysis.describe(symbol).into())
+        ])
+    }).collect();
+
+    let mut keywords: Vec<String> = Keyword::hash_map().into_keys().collect();
+    keywords.extend(["true", "false", "none"].map(String::from));
+    keywords.sort();
+    items.extend(keywords.into_iter().map(|keyword| Json::object(vec![
+        ("label", keyword.into()),
+        ("kind", COMPLETION_KEYWORD.into())
+    ])));
+
+    Json::Array(items)
+}
+
+/// One edit replacing the whole text, none if it's already formatted, `null` if it doesn't parse.
+fn formatting(document: &Document) -> Json {
+    match Formatter::format(&document.text) {
+        Ok(formatted) if formatted == document.text => Json::Array(Vec::new()),
+        Ok(formatted) => Json::Array(vec![Json::object(vec![
+            ("range", document.range(0, document.chars.len())),
+            ("newText", formatted.into())
+        ])]),
+        Err(_) => Json::Null,
+    }
+}
+
+/// Classified spans encoded as LSP semantic tokens, five numbers each relative to the previous one.
+fn semantic_tokens(document: &Document) -> Json {
+    let classes = TokenClass::classes();
+    let mut data: Vec<usize> = Vec::new();
+    let (mut pos, mut line, mut character) = (0, 0, 0);
+    let (mut last_line, mut last_character) = (0, 0);
+
+    for span in classify(&document.text) {
+        let class = classes.iter().position(|class| class == &span.get_class()).unwrap_or(0);
+
+        // tokens can't span lines, a string or block comment over several lines is sent line by line
+        let mut start = span.get_start();
+        while start < span.get_end() {
+            for c in &document.chars[pos..start] {
+                if c == &'\n' {
+                    (line, character) = (line + 1, 0)
+                } else {
+                    character += c.len_utf16()
+                }
+            }
+            pos = start;
+
+            let end = document.chars[start..span.get_end()].iter().position(|c| c == &'\n').map_or(span.get_end(), |newline| start + newline);
+            let length: usize = document.chars[start..end].iter().map(|c| c.len_utf16()).sum();
+            if length > 0 {
+                let delta = if line == last_line { character - last_character } else { character };
+                data.extend([line - last_line, delta, length, class, 0]);
+                (last_line, last_character) = (line, character);
+            }
+
+            start = end + 1
+        }
+    }
+
+    Json::object(vec![("data", Json::Array(data.into_iter().map(Json::from).collect()))])
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Json) -> io::Result<()> {
+    send(&Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/publishDiagnostics".into()),
+        ("params", Json::object(vec![("uri", uri.into()), ("diagnostics", diagnostics)]))
+    ]))
+}
+
+fn error_response(id: Json, code: f64, message: &str) -> Json {
+    Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("id", id),
+        ("error", Json::object(vec![("code", Json::Number(code)), ("message", message.into())]))
+    ])
+}
+
+/// Reads the body of the next `Content-Length` framed message, `None` once stdin is closed.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message without a Content-Length header"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    String::from_utf8(body).map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn send(message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    let mut stdout = io::stdout().lock();
+
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    stdout.flush()
+}
\ No newline at end of file
@@ -1,58 +1,199 @@
-use std::io::{self, Write};
-use std::env;
+#![allow(clippy::to_string_trait_impl, clippy::upper_case_acronyms, clippy::match_like_matches_macro)]
 
+use std::io::{self, IsTerminal};
+use std::{env, fs, process, thread};
+
+use cst::SyntaxNode;
+use error_code::ErrorCode;
+use formatter::Formatter;
 use interpreter::Interpreter;
+use lint::Lint;
+use lsp::Server;
+use types::Type;
 
 mod symbol_table;
+mod error_code;
+mod error_value;
 mod interpreter;
 mod variable;
+mod function;
+mod builtin;
 mod keyword;
 mod context;
 mod parser;
+mod pattern;
+mod cst;
+mod formatter;
+mod json;
+mod analysis;
+mod lsp;
+mod highlight;
+mod line_editor;
+mod repl;
+mod signal;
 mod token;
 mod lexer;
+mod source;
+mod lint;
+mod integer;
+mod rational;
+mod range;
+mod decimal;
 mod types;
 mod node;
 mod op;
 
+/// Evaluation recurses over the node tree, so deep kumi call stacks need a large native stack.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("error spawning interpreter thread");
+
+    if interpreter.join().is_err() {
+        process::exit(101)
+    }
+}
+
+fn run() {
+    let mut args: Vec<String> = env::args().collect();
+    let deny_warnings = take_flag(&mut args, "--deny-warnings");
+    let check = take_flag(&mut args, "--check");
     if args.len() < 2 {
-        run_cli()
+        // a script piped into kumi runs like a file
+        if !io::stdin().is_terminal() {
+            return run_file("-", deny_warnings);
+        }
+        return repl::run(deny_warnings);
     }
 
-    run_file(&args[1])
+    match args[1].as_str() {
+        "--explain" => explain(args.get(2).map(|code| code.as_str())),
+        "fmt" => format_files(&args[2..], check),
+        "lsp" => serve(),
+        "--cst" => print_cst(args.get(2).map(|path| path.as_str())),
+        path => run_file(path, deny_warnings)
+    }
 }
 
-fn run_file(_: &str) {
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != flag);
+
+    args.len() != len
 }
 
-fn run_cli() {
-    let mut interpreter = Interpreter::new("").expect("error creating interpreter");
-    loop {
-        print!("kumi> ");
-        io::stdout().flush().expect("flush error");
-        
-        let mut input = String::new();
-        if let Err(err) = io::stdin().read_line(&mut input) {
-            println!("{}", err);
-            continue
-        };
-        let input = input.trim();
-
-        if let Err(err) = interpreter.update(input) {
-            println!("{}", err);
-            continue
-        };
-
-        let result = match interpreter.run() {
-            Ok(result) => result,
+fn explain(code: Option<&str>) {
+    let code = match code {
+        Some(code) => code,
+        None => {
+            eprintln!("usage: kumi --explain <code>, for example `kumi --explain K0003`");
+            process::exit(2)
+        }
+    };
+
+    if let Some(error_code) = ErrorCode::from_code(code) {
+        println!("{}\nkind: {}\n\n{}", error_code.to_string(), error_code.name(), error_code.explanation())
+    } else if let Some(lint) = Lint::from_code(code) {
+        println!("{}\n\n{}", lint.to_string(), lint.explanation())
+    } else {
+        eprintln!("{} isn\'t a valid error code", code);
+        process::exit(1)
+    }
+}
+
+/// Prints the concrete syntax tree of a file, tokens with the comments and whitespace around them.
+fn print_cst(path: Option<&str>) {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: kumi --cst <file>");
+            process::exit(2)
+        }
+    };
+
+    let result = fs::read_to_string(path).and_then(|text| SyntaxNode::parse(&text));
+    match result {
+        Ok(tree) => print!("{:?}", tree),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    }
+}
+fn serve() {
+    match Server::new().run() {
+        Ok(status) => process::exit(status),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    }
+}
+
+/// Rewrites files in the canonical style, with `check` only reports the files that would change.
+fn format_files(paths: &[String], check: bool) {
+    if paths.is_empty() {
+        eprintln!("usage: kumi fmt [--check] <file>...");
+        process::exit(2)
+    }
+
+    let mut failed = false;
+    for path in paths {
+        let result = fs::read_to_string(path).and_then(|text| {
+            let formatted = Formatter::format(&text)?;
+            Ok((formatted != text, formatted))
+        });
+
+        match result {
+            Ok((false, _)) => {},
+            Ok((true, _)) if check => {
+                println!("would reformat {}", path);
+                failed = true
+            },
+            Ok((true, formatted)) => if let Err(err) = fs::write(path, formatted) {
+                eprintln!("can\'t write {}: {}", path, err);
+                failed = true
+            },
             Err(err) => {
-                println!("{}", err);
-                continue
+                eprintln!("{}: {}", path, err);
+                failed = true
             },
-        };
+        }
+    }
+
+    if failed {
+        process::exit(1)
+    }
+}
+
+/// Runs the file at `path`, `-` reads the program from stdin.
+fn run_file(path: &str, deny_warnings: bool) {
+    let (name, text) = match path {
+        "-" => ("<stdin>", io::read_to_string(io::stdin())),
+        path => (path, fs::read_to_string(path)),
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("can\'t read {}: {}", path, err);
+            process::exit(1)
+        }
+    };
+
+    let result = Interpreter::new(name, &text).and_then(|mut interpreter| {
+        interpreter.set_deny_warnings(deny_warnings);
+        interpreter.run()
+    });
 
-        println!("{:?}", result)
+    match result {
+        Ok(Type::None) => {},
+        Ok(result) => println!("{:?}", result),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
     }
 }
\ No newline at end of file
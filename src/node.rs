@@ -1,41 +1,574 @@
-use crate::{types::Type, variable::Variable, context::Context, op::Op};
-use std::io;
+use crate::{types::Type, variable::Variable, context::Context, op::Op, error_code::ErrorCode, token::Token, function::Function};
+use crate::error_code::CodedError;
+use crate::error_value::ErrorValue;
+use crate::integer::Integer;
+use crate::pattern::Pattern;
+use crate::range::Range;
+use crate::signal;
+use std::fmt::{self, Debug};
+use std::{io, rc::Rc};
 
 #[derive(Clone)]
 pub enum Node {
-    Number(Type),
-    GetVar(String),
-    NotOp(Box<Node>),
-    UnaryOp(Box<Node>),
-    DeclareVar(String, Box<Node>),
-    BinOp(Box<Node>, Op, Box<Node>),
+    Number(Type, Token),
+    GetVar(String, Token),
+    NotOp(Box<Node>, Token),
+    UnaryOp(Box<Node>, Token),
+    BitNotOp(Box<Node>, Token),
+    DeclareVar(String, Box<Node>, Token),
+    DeclareFn(String, Vec<String>, Rc<Node>, Option<String>, Token),
+    BinOp(Box<Node>, Op, Box<Node>, Token),
+    LogicOp(Box<Node>, Op, Box<Node>, Token),
+    IsNone(Box<Node>),
+    Call(Box<Node>, Vec<Node>, Token),
+    GetMember(Box<Node>, String, bool, Token),
+    CallMethod(Box<Node>, String, Vec<Node>, bool, Token),
+    Index(Box<Node>, Box<Node>, Token),
+    List(Vec<Node>, Token),
+    Range(Box<Node>, Box<Node>, Option<Box<Node>>, bool, Token),
+    Unwrap(Box<Node>, Token),
+    Return(Option<Box<Node>>, Token),
+    Throw(Box<Node>, Token),
+    Try(Box<Node>, Vec<Catch>, Option<Box<Node>>, Token),
+    Match(Box<Node>, Vec<Arm>, Token),
+    If(Box<Node>, Box<Node>, Option<Box<Node>>, Token),
+    For(String, Box<Node>, Box<Node>, Token),
+    Break(Token),
+    Continue(Token),
+    Statements(Vec<Node>),
+}
+
+/// `catch Kind | OtherKind name { body }`, without kinds it catches every error.
+#[derive(Clone)]
+pub struct Catch {
+    kinds: Vec<String>,
+    name: String,
+    body: Node,
+    /// The token of `name`, not of the `catch` keyword.
+    token: Token
+}
+
+impl Catch {
+    pub fn new(kinds: Vec<String>, name: String, body: Node, token: Token) -> Self {
+        Self { kinds, name, body, token }
+    }
+
+    pub fn get_kinds(&self) -> &[String] {
+        &self.kinds
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_body(&self) -> &Node {
+        &self.body
+    }
+
+    pub fn get_token(&self) -> &Token {
+        &self.token
+    }
+
+    fn matches(&self, error: &ErrorValue) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|kind| kind == error.get_kind())
+    }
+}
+
+/// `pattern => body` inside a `match`.
+#[derive(Clone)]
+pub struct Arm {
+    pattern: Pattern,
+    body: Node
+}
+
+impl Arm {
+    pub fn new(pattern: Pattern, body: Node) -> Self {
+        Self { pattern, body }
+    }
+
+    pub fn get_pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    pub fn get_body(&self) -> &Node {
+        &self.body
+    }
+}
+
+/// Reasons for evaluation to leave a node before it produced its value.
+pub enum Interrupt {
+    Return(Type),
+    Break,
+    Continue,
+    Error(io::Error)
+}
+
+impl From<io::Error> for Interrupt {
+    fn from(err: io::Error) -> Self {
+        Interrupt::Error(err)
+    }
 }
 
 impl Node {
     pub fn get_value(&self, context: &mut Context) -> io::Result<Type> {
+        match self.eval(context) {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            // the parser keeps `break` and `continue` inside loops
+            Err(Interrupt::Break | Interrupt::Continue) => Ok(Type::None),
+            Err(Interrupt::Error(err)) => Err(err),
+        }
+    }
+
+    pub fn eval(&self, context: &mut Context) -> Result<Type, Interrupt> {
         match self {
-            Node::Number(type_) => Ok(type_.clone()),
-            Node::UnaryOp(node) => (&*node).get_value(context)?.inverted(),
-            Node::DeclareVar(name, value) => {
-                let variable = Variable::new(name, (&**value).clone(), context)?;
+            Node::Number(type_, _) => Ok(type_.clone()),
+            Node::UnaryOp(node, token) => {
+                let value = node.eval(context)?;
+                context.set_pos(token);
+
+                Ok(value.inverted()?)
+            },
+            Node::BitNotOp(node, token) => {
+                let value = node.eval(context)?;
+                context.set_pos(token);
+
+                Ok(value.bit_not()?)
+            },
+            Node::DeclareVar(name, value, _) => {
+                let value = value.eval(context)?;
+
+                context.get_symbol_table_mut().declare(Variable::new(name, value));
+
+                Ok(Type::None)
+            },
+            Node::DeclareFn(name, params, body, doc, _) => {
+                let function = Function::new(
+                    name,
+                    params.clone(),
+                    body.clone(),
+                    doc.clone(),
+                    context.get_source().clone(),
+                    context.get_symbol_table().get_id()
+                );
 
-                context.get_symbol_table_mut().declare(variable);
+                context.get_symbol_table_mut().declare(Variable::new(name, Type::Function(Rc::new(function))));
 
                 Ok(Type::None)
             },
-            Node::BinOp(left, op_token, right) => op_token.execute(&(&*left).get_value(context)?, &(&*right).get_value(context)?),
-            Node::GetVar(name) => {
-                let variable = context.get_symbol_table().get_from_tree(name)?.clone();
+            Node::BinOp(left, op_token, right, token) => {
+                let left = left.eval(context)?;
+                let right = right.eval(context)?;
+                context.set_pos(token);
+
+                Ok(op_token.execute(&left, &right)?)
+            },
+            Node::LogicOp(left, op_token, right, token) => {
+                let left = left.eval(context)?;
+                context.set_pos(token);
+
+                // `x ?? default` evaluates `default` only when `x` is none
+                if let Op::Coalesce = op_token {
+                    return match left {
+                        Type::None => right.eval(context),
+                        _ => Ok(left),
+                    };
+                }
+
+                // `false && ...` and `true || ...` are decided without evaluating the right operand
+                match (op_token, &left) {
+                    (Op::And, Type::Bool(false)) | (Op::Or, Type::Bool(true)) => return Ok(left),
+                    (_, Type::Bool(_)) => {},
+                    _ => return Err(ErrorCode::TypeMismatch.error(format!(
+                        "can\'t execute {} on {}, the left operand must be a bool", op_token.to_string(), left.to_string()
+                    )).into())
+                }
+
+                let right = right.eval(context)?;
+                context.set_pos(token);
+
+                Ok(op_token.execute(&left, &right)?)
+            },
+            Node::IsNone(node) => Ok(Type::Bool(matches!(node.eval(context)?, Type::None))),
+            Node::GetVar(name, token) => {
+                context.set_pos(token);
+
+                let variable = context.get_symbol_table().get_from_tree(name)?;
                 Ok(variable.get_value().clone())
             },
-            Node::NotOp(node) => {
-                let value = (&*node).get_value(context)?;
-                value.ensure_bool(&Type::Bool(true), |a, _| Type::Bool(!a))
-                    .map_or(Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        format!("can\'t execute not on not a bool type {}", value.to_string())
-                    )), |val| Ok(val))
+            Node::NotOp(node, token) => {
+                let value = node.eval(context)?;
+                context.set_pos(token);
+
+                Ok(value.ensure_bool(&Type::Bool(true), |a, _| Type::Bool(!a)).ok_or(ErrorCode::BadUnaryOperand.error(
+                    format!("can\'t execute not on not a bool type {}", value.to_string())
+                ))?)
+            },
+            Node::Call(callee, args, token) => {
+                let callee = callee.eval(context)?;
+
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(arg.eval(context)?);
+                }
+                context.set_pos(token);
+
+                match callee {
+                    Type::Function(function) => Ok(function.call(values, context)?),
+                    Type::Builtin(builtin) => Ok(builtin.call(values)?),
+                    _ => Err(ErrorCode::NotCallable.error(format!("{} isn\'t callable", callee.to_string())).into())
+                }
+            },
+            Node::GetMember(node, name, optional, token) => {
+                let value = node.eval(context)?;
+                context.set_pos(token);
+
+                match value {
+                    Type::None if *optional => Ok(Type::None),
+                    _ => Ok(value.get_member(name)?),
+                }
+            },
+            Node::CallMethod(node, name, args, optional, token) => {
+                let value = node.eval(context)?;
+                if let (Type::None, true) = (&value, optional) {
+                    return Ok(Type::None);
+                }
+
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(arg.eval(context)?);
+                }
+                context.set_pos(token);
+
+                Ok(value.call_method(name, values)?)
+            },
+            Node::Index(node, index, token) => {
+                let value = node.eval(context)?;
+                let index = index.eval(context)?;
+                context.set_pos(token);
+
+                Ok(value.index(&index)?)
+            },
+            Node::List(items, _) => {
+                let mut values = Vec::new();
+                for item in items {
+                    values.push(item.eval(context)?);
+                }
+
+                Ok(Type::List(Rc::new(values)))
+            },
+            Node::Range(start, end, step, inclusive, token) => {
+                let start = start.eval(context)?;
+                let end = end.eval(context)?;
+                let step = match step {
+                    Some(step) => step.eval(context)?,
+                    None => Type::Int(Integer::from(1)),
+                };
+                context.set_pos(token);
+
+                let (start, end, step) = match (start, end, step) {
+                    (Type::Int(start), Type::Int(end), Type::Int(step)) => (start, end, step),
+                    (start, end, step) => return Err(ErrorCode::TypeMismatch.error(format!(
+                        "can\'t make a range of {}, {} and step {}, all of them must be ints", start.to_string(), end.to_string(), step.to_string()
+                    )).into())
+                };
+
+                let range = Range::new(start, end, step, *inclusive).ok_or_else(
+                    || ErrorCode::ZeroStep.error("can\'t make a range with step 0")
+                )?;
+
+                Ok(Type::Range(Rc::new(range)))
+            },
+            Node::Unwrap(node, token) => {
+                let value = node.eval(context)?;
+                context.set_pos(token);
+
+                // `value?` hands an `err` to the caller of the enclosing function as is
+                match value {
+                    Type::Ok(value) => Ok(*value),
+                    Type::Err(_) => Err(Interrupt::Return(value)),
+                    _ => Err(ErrorCode::TypeMismatch.error(format!(
+                        "can\'t apply ? to {}, only ok and err values can be unwrapped", value.to_string()
+                    )).into())
+                }
+            },
+            Node::Throw(node, token) => {
+                let value = node.eval(context)?;
+                context.set_pos(token);
+
+                let error = match value {
+                    Type::Error(error) => (*error).clone(),
+                    value => ErrorValue::wrap(value),
+                };
+
+                Err(error.throw(context.span()).into())
+            },
+            Node::Try(body, catches, finally, _) => {
+                let result = match body.eval(context) {
+                    // Ctrl-C has to reach the REPL
+                    Err(Interrupt::Error(err)) if !CodedError::of(&err).is_some_and(|err| err.get_code() == ErrorCode::Interrupted) => {
+                        let error = ErrorValue::from_error(&err, context);
+
+                        match catches.iter().find(|catch| catch.matches(&error)) {
+                            Some(catch) => context.scoped(|scope| {
+                                scope.get_symbol_table_mut().declare(Variable::new(&catch.name, Type::Error(Rc::new(error))));
+                                catch.body.eval(scope)
+                            }),
+                            None => Err(Interrupt::Error(err)),
+                        }
+                    },
+                    result => result,
+                };
+
+                // an error or a return from `finally` replaces the outcome of the blocks before it
+                if let Some(finally) = finally {
+                    finally.eval(context)?;
+                }
+
+                result
+            },
+            Node::Match(node, arms, token) => {
+                let value = node.eval(context)?;
+
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    if !arm.pattern.matches(&value, &mut bindings) {
+                        continue
+                    }
+
+                    return context.scoped(|scope| {
+                        for (name, value) in bindings {
+                            scope.get_symbol_table_mut().declare(Variable::new(&name, value));
+                        }
+
+                        arm.body.eval(scope)
+                    });
+                }
+
+                context.set_pos(token);
+                Err(ErrorCode::NoMatchingArm.error(format!("no arm of the match accepts {}", value.to_string())).into())
+            },
+            Node::If(condition, then, otherwise, token) => {
+                let condition = condition.eval(context)?;
+                context.set_pos(token);
+
+                match (condition, otherwise) {
+                    (Type::Bool(true), _) => then.eval(context),
+                    (Type::Bool(false), Some(otherwise)) => otherwise.eval(context),
+                    (Type::Bool(false), None) => Ok(Type::None),
+                    (condition, _) => Err(ErrorCode::TypeMismatch.error(format!(
+                        "can\'t use {} as a condition, it must be a bool", condition.to_string()
+                    )).into())
+                }
+            },
+            Node::For(name, iterable, body, token) => {
+                let iterable = iterable.eval(context)?;
+                context.set_pos(token);
+
+                for value in iterable.iter()? {
+                    signal::check_interrupt()?;
+                    context.get_symbol_table_mut().declare(Variable::new(name, value));
+
+                    match body.eval(context) {
+                        Ok(_) | Err(Interrupt::Continue) => {},
+                        Err(Interrupt::Break) => break,
+                        Err(err) => return Err(err),
+                    }
+                }
+
+                Ok(Type::None)
+            },
+            Node::Break(_) => Err(Interrupt::Break),
+            Node::Continue(_) => Err(Interrupt::Continue),
+            Node::Return(value, _) => {
+                let value = match value {
+                    Some(value) => value.eval(context)?,
+                    None => Type::None,
+                };
+
+                Err(Interrupt::Return(value))
+            },
+            Node::Statements(statements) => {
+                let mut result = Type::None;
+
+                for statement in statements {
+                    result = statement.eval(context)?;
+                }
+
+                Ok(result)
             }
         }
     }
+
+    /// The leftmost token of the node, used to point at a whole statement.
+    pub fn first_token(&self) -> Option<&Token> {
+        match self {
+            Node::Number(_, token) |
+            Node::GetVar(_, token) |
+            Node::NotOp(_, token) |
+            Node::UnaryOp(_, token) |
+            Node::BitNotOp(_, token) |
+            Node::DeclareVar(_, _, token) |
+            Node::DeclareFn(_, _, _, _, token) |
+            Node::Return(_, token) |
+            Node::Throw(_, token) |
+            Node::Try(_, _, _, token) |
+            Node::Match(_, _, token) |
+            Node::If(_, _, _, token) |
+            Node::List(_, token) |
+            Node::For(_, _, _, token) |
+            Node::Break(token) |
+            Node::Continue(token) => Some(token),
+            Node::BinOp(left, _, _, _) | Node::LogicOp(left, _, _, _) | Node::IsNone(left) => left.first_token(),
+            Node::Call(callee, _, _) |
+            Node::GetMember(callee, _, _, _) |
+            Node::CallMethod(callee, _, _, _, _) |
+            Node::Index(callee, _, _) |
+            Node::Range(callee, _, _, _, _) |
+            Node::Unwrap(callee, _) => callee.first_token(),
+            Node::Statements(statements) => statements.first().and_then(|statement| statement.first_token()),
+        }
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let line = |f: &mut fmt::Formatter<'_>, depth: usize, text: String| writeln!(f, "{}{}", "  ".repeat(depth), text);
+        let optional = |optional: &bool| if *optional { "?." } else { "." };
+
+        let (label, children): (String, Vec<&Node>) = match self {
+            Node::Number(value, _) => (format!("Number {:?}", value), vec![]),
+            Node::GetVar(name, _) => (format!("GetVar {}", name), vec![]),
+            Node::NotOp(node, _) => ("NotOp !".to_string(), vec![node]),
+            Node::UnaryOp(node, _) => ("UnaryOp -".to_string(), vec![node]),
+            Node::BitNotOp(node, _) => ("BitNotOp ~".to_string(), vec![node]),
+            Node::DeclareVar(name, value, _) => (format!("DeclareVar {}", name), vec![value]),
+            Node::DeclareFn(name, params, body, _, _) => (format!("DeclareFn {}({})", name, params.join(", ")), vec![body]),
+            Node::BinOp(left, op, right, _) => (format!("BinOp {}", op.to_string()), vec![left, right]),
+            Node::LogicOp(left, op, right, _) => (format!("LogicOp {}", op.to_string()), vec![left, right]),
+            Node::IsNone(node) => ("IsNone".to_string(), vec![node]),
+            Node::Call(callee, args, _) => ("Call".to_string(), [callee.as_ref()].into_iter().chain(args).collect()),
+            Node::GetMember(node, name, optional_, _) => (format!("GetMember {}{}", optional(optional_), name), vec![node]),
+            Node::CallMethod(node, name, args, optional_, _) => (
+                format!("CallMethod {}{}", optional(optional_), name),
+                [node.as_ref()].into_iter().chain(args).collect()
+            ),
+            Node::Index(node, index, _) => ("Index".to_string(), vec![node, index]),
+            Node::List(items, _) => ("List".to_string(), items.iter().collect()),
+            Node::Range(start, end, step, inclusive, _) => (
+                format!("Range {}", if *inclusive { "..=" } else { ".." }),
+                [start.as_ref(), end.as_ref()].into_iter().chain(step.as_deref()).collect()
+            ),
+            Node::Unwrap(node, _) => ("Unwrap ?".to_string(), vec![node]),
+            Node::Return(value, _) => ("Return".to_string(), value.as_deref().into_iter().collect()),
+            Node::Throw(value, _) => ("Throw".to_string(), vec![value]),
+            Node::Try(body, catches, finally, _) => {
+                line(f, depth, "Try".to_string())?;
+                body.write_tree(f, depth + 1)?;
+                for catch in catches {
+                    let kinds = catch.get_kinds().join(" | ");
+                    line(f, depth + 1, format!("Catch {}{}", if kinds.is_empty() { kinds } else { kinds + " " }, catch.get_name()))?;
+                    catch.get_body().write_tree(f, depth + 2)?;
+                }
+                if let Some(finally) = finally {
+                    line(f, depth + 1, "Finally".to_string())?;
+                    finally.write_tree(f, depth + 2)?;
+                }
+
+                return Ok(());
+            },
+            Node::Match(value, arms, _) => {
+                line(f, depth, "Match".to_string())?;
+                value.write_tree(f, depth + 1)?;
+                for arm in arms {
+                    line(f, depth + 1, format!("Arm {}", arm.get_pattern().to_string()))?;
+                    arm.get_body().write_tree(f, depth + 2)?;
+                }
+
+                return Ok(());
+            },
+            Node::If(condition, then, otherwise, _) => ("If".to_string(), [condition.as_ref(), then.as_ref()].into_iter().chain(otherwise.as_deref()).collect()),
+            Node::For(name, iterable, body, _) => (format!("For {}", name), vec![iterable, body]),
+            Node::Break(_) => ("Break".to_string(), vec![]),
+            Node::Continue(_) => ("Continue".to_string(), vec![]),
+            Node::Statements(statements) => ("Statements".to_string(), statements.iter().collect()),
+        };
+
+        line(f, depth, label)?;
+        for child in children {
+            child.write_tree(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An indented tree of the nodes, one per line.
+impl Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::interpreter::Interpreter;
+
+    fn eval(text: &str) -> io::Result<String> {
+        Interpreter::new("<test>", text).and_then(|mut interpreter| interpreter.run()).map(|value| format!("{:?}", value))
+    }
+
+    #[test]
+    fn logical_truth_tables() {
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert_eq!(eval(&format!("{} && {}", a, b)).unwrap(), format!("bool({})", a && b));
+            assert_eq!(eval(&format!("{} || {}", a, b)).unwrap(), format!("bool({})", a || b));
+        }
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        assert_eq!(eval("let x = 0\nx != 0 && 10 / x > 1").unwrap(), "bool(false)");
+        assert_eq!(eval("let x = 0\nx == 0 || 10 / x > 1").unwrap(), "bool(true)");
+        // the right operand isn't evaluated, so its type isn't checked either
+        assert_eq!(eval("false && 1").unwrap(), "bool(false)");
+        assert_eq!(eval("true || 1").unwrap(), "bool(true)");
+    }
+
+    #[test]
+    fn logical_operators_check_the_left_operand() {
+        let err = eval("1 && true").unwrap_err().to_string();
+        assert!(err.contains("[K0003]") && err.contains("the left operand must be a bool"), "{}", err);
+
+        let err = eval("none || false").unwrap_err().to_string();
+        assert!(err.contains("[K0003]") && err.contains("the left operand must be a bool"), "{}", err);
+
+        assert!(eval("true && 1").unwrap_err().to_string().contains("[K0003]"));
+    }
+
+    #[test]
+    fn catch_names_stay_in_their_body() {
+        assert_eq!(eval("try { 1/0 } catch e { e.kind }").unwrap(), "str(\"DivisionByZero\")");
+        assert!(eval("try { 1/0 } catch e { 0 }\ne.kind").unwrap_err().to_string().contains("[K0014]"));
+        assert!(eval("try { 1/0 } catch e { let z = 3 }\nz").unwrap_err().to_string().contains("[K0014]"));
+        assert_eq!(eval("let e = 7\ntry { 1/0 } catch e { 0 }\ne").unwrap(), "int(7)");
+    }
+
+    #[test]
+    fn any_value_can_be_thrown() {
+        assert_eq!(eval("try { throw 5 } catch e { e.message }").unwrap(), "str(\"int(5)\")");
+        assert_eq!(eval("try { throw [1, 2] } catch e { e.value[1] }").unwrap(), "int(2)");
+        assert_eq!(eval("try { throw \"no\" } catch e { e.message + e.kind }").unwrap(), "str(\"noError\")");
+        assert_eq!(eval("try { 1/0 } catch e { e.value }").unwrap(), "none");
+        assert_eq!(eval("try { try { throw 5 } catch e { throw e } } catch e { e.value + 1 }").unwrap(), "int(6)");
+
+        let err = eval("throw true").unwrap_err().to_string();
+        assert!(err.contains("[K0026] Error: bool(true)"), "{}", err);
+    }
+
+    #[test]
+    fn match_bindings_stay_in_their_arm() {
+        assert_eq!(eval("match ok(3) { ok(v) => v * 2, _ => 1 }").unwrap(), "int(6)");
+        assert!(eval("match ok(3) { ok(v) => 0, _ => 1 }\nv").unwrap_err().to_string().contains("[K0014]"));
+        assert_eq!(eval("let v = 7\nmatch ok(3) { ok(v) => 0, _ => 1 }\nv").unwrap(), "int(7)");
+    }
 }
\ No newline at end of file
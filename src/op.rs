@@ -1,3 +1,4 @@
+use crate::error_code::ErrorCode;
 use crate::types::Type;
 use std::io;
 
@@ -6,6 +7,7 @@ pub enum Op {
     Add,
     Sub,
     Devd,
+    FloorDevd,
     Mult,
     Pow,
     Remain,
@@ -17,7 +19,14 @@ pub enum Op {
     GT,
     GTE,
     And,
-    Or
+    Or,
+    Coalesce,
+    BitAnd,
+    BitOr,
+    Xor,
+    Shl,
+    Shr,
+    In
 }
 
 impl Op {
@@ -26,6 +35,7 @@ impl Op {
             Op::Add => a.add(b),
             Op::Sub => a.sub(b),
             Op::Devd => a.devd(b),
+            Op::FloorDevd => a.floor_devd(b),
             Op::Mult => a.mult(b),
             Op::Pow => a.pow(b),
             Op::Remain => a.remain(b),
@@ -33,8 +43,17 @@ impl Op {
             Op::Or => a.or(b),
             Op::Eq => a.eq(b),
             Op::NE => a.eq(b).map(|b| b.inverted())?,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
+            Op::LT => a.lt(b),
+            Op::LTE => a.lte(b),
+            Op::GT => a.gt(b),
+            Op::GTE => a.gte(b),
+            Op::BitAnd => a.bit_and(b),
+            Op::BitOr => a.bit_or(b),
+            Op::Xor => a.xor(b),
+            Op::Shl => a.shl(b),
+            Op::Shr => a.shr(b),
+            Op::In => b.contains(a).map(Type::Bool),
+            _ => Err(ErrorCode::UnsupportedOperator.error(
                 format!("can\'t execute {} on {} and {}", self.to_string(), a.to_string(), b.to_string())
             ))
         }
@@ -42,7 +61,7 @@ impl Op {
 
     pub fn logical(&self) -> bool {
         match self {
-            Op::Eq | Op::NE | Op::LT | Op::LTE | Op::GT | Op::GTE => true,
+            Op::Eq | Op::NE | Op::LT | Op::LTE | Op::GT | Op::GTE | Op::In => true,
             _ => false
         }
     }
@@ -54,6 +73,7 @@ impl ToString for Op {
             Op::Add => "+".to_string(),
             Op::Sub => "-".to_string(),
             Op::Devd => "/".to_string(),
+            Op::FloorDevd => "//".to_string(),
             Op::Mult => "*".to_string(),
             Op::Pow => "^".to_string(),            
             Op::Remain => "%".to_string(),
@@ -66,6 +86,124 @@ impl ToString for Op {
             Op::GTE => ">=".to_string(),
             Op::And => "&&".to_string(),
             Op::Or => "||".to_string(),
+            Op::Coalesce => "??".to_string(),
+            Op::BitAnd => "&".to_string(),
+            Op::BitOr => "|".to_string(),
+            Op::Xor => "~".to_string(),
+            Op::Shl => "<<".to_string(),
+            Op::Shr => ">>".to_string(),
+            Op::In => "in".to_string(),
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::Interpreter;
+
+    fn eval(text: &str) -> String {
+        let result = Interpreter::new("<test>", text).and_then(|mut interpreter| interpreter.run());
+        match result {
+            Ok(value) => format!("{:?}", value),
+            Err(err) => panic!("{} failed: {}", text, err),
+        }
+    }
+
+    /// The error code `text` fails with.
+    fn error(text: &str) -> String {
+        match Interpreter::new("<test>", text).and_then(|mut interpreter| interpreter.run()) {
+            Ok(value) => panic!("{} evaluated to {:?}", text, value),
+            Err(err) => {
+                let message = err.to_string();
+                let start = message.rfind("[K").expect("a coded error");
+                message[start + 1..start + 6].to_string()
+            },
+        }
+    }
+
+    #[test]
+    fn arithmetic_promotes_int_to_float() {
+        assert_eq!(eval("1 + 2.5"), "float(3.5)");
+        assert_eq!(eval("2.5 + 1"), "float(3.5)");
+        assert_eq!(eval("1 - 0.5"), "float(0.5)");
+        assert_eq!(eval("0.5 - 1"), "float(-0.5)");
+        assert_eq!(eval("2 * 1.5"), "float(3)");
+        assert_eq!(eval("1.5 * 2"), "float(3)");
+        assert_eq!(eval("3 / 2.0"), "float(1.5)");
+        assert_eq!(eval("3.0 / 2"), "float(1.5)");
+        assert_eq!(eval("4.0 ^ 2"), "float(16)");
+        assert_eq!(eval("2 ^ 0.5"), "float(1.4142135623730951)");
+        assert_eq!(eval("7 % 2.5"), "float(2)");
+        assert_eq!(eval("7.5 % 2"), "float(1.5)");
+    }
+
+    #[test]
+    fn ints_stay_ints() {
+        assert_eq!(eval("1 + 2"), "int(3)");
+        assert_eq!(eval("1 - 2"), "int(-1)");
+        assert_eq!(eval("2 * 3"), "int(6)");
+        assert_eq!(eval("2 ^ 10"), "int(1024)");
+        assert_eq!(eval("7 % 3"), "int(1)");
+        assert_eq!(eval("7 // 2"), "int(3)");
+    }
+
+    #[test]
+    fn comparisons_across_int_and_float() {
+        assert_eq!(eval("1 == 1.0"), "bool(true)");
+        assert_eq!(eval("1.0 == 1"), "bool(true)");
+        assert_eq!(eval("1 != 1.5"), "bool(true)");
+        assert_eq!(eval("1 != 1.0"), "bool(false)");
+        assert_eq!(eval("1 < 1.5"), "bool(true)");
+        assert_eq!(eval("1.5 < 1"), "bool(false)");
+        assert_eq!(eval("1 <= 1.0"), "bool(true)");
+        assert_eq!(eval("2 > 1.5"), "bool(true)");
+        assert_eq!(eval("2.5 > 3"), "bool(false)");
+        assert_eq!(eval("2 >= 2.0"), "bool(true)");
+        assert_eq!(eval("1 in [1.0]"), "bool(true)");
+        assert_eq!(eval("1.0 in [1]"), "bool(true)");
+        assert_eq!(eval("1.0 in 0..3"), "bool(true)");
+    }
+
+    #[test]
+    fn int_only_operators_reject_floats() {
+        assert_eq!(eval("5 & 3"), "int(1)");
+        assert_eq!(eval("5 | 3"), "int(7)");
+        assert_eq!(eval("5 ~ 3"), "int(6)");
+        assert_eq!(eval("1 << 3"), "int(8)");
+        assert_eq!(eval("8 >> 1"), "int(4)");
+
+        for text in ["7.0 // 2", "7 // 2.0", "5 & 1.0", "1.5 | 1", "1 ~ 1.0", "1.0 << 1", "4 >> 1.0"] {
+            assert_eq!(error(text), "K0003", "{}", text);
+        }
+    }
+
+    #[test]
+    fn logical_operators_reject_numbers() {
+        assert_eq!(error("true && 1"), "K0003");
+        assert_eq!(error("1 || true"), "K0003");
+        assert_eq!(error("!1.0"), "K0012");
+        assert_eq!(eval("none ?? 2.5"), "float(2.5)");
+        assert_eq!(eval("1 ?? 2.0"), "int(1)");
+    }
+
+    #[test]
+    fn explicit_conversions() {
+        assert_eq!(eval("int(2.7)"), "int(2)");
+        assert_eq!(eval("int(-2.7)"), "int(-2)");
+        assert_eq!(eval("int(3)"), "int(3)");
+        assert_eq!(eval("float(3)"), "float(3)");
+        assert_eq!(eval("float(2.5)"), "float(2.5)");
+        assert_eq!(eval("int(2.5) + float(1)"), "float(3)");
+        assert_eq!(error("int(\"12\")"), "K0021");
+    }
+
+    #[test]
+    fn floor_division_rounds_down_but_remainder_truncates() {
+        assert_eq!(eval("7 // 2"), "int(3)");
+        assert_eq!(eval("-7 // 2"), "int(-4)");
+        assert_eq!(eval("7 // -2"), "int(-4)");
+        assert_eq!(eval("-7 % 2"), "int(-1)");
+        assert_eq!(eval("7 % -2"), "int(1)");
+        assert_eq!(eval("-7.5 % 2"), "float(-1.5)");
+    }
 }
\ No newline at end of file
@@ -1,10 +1,16 @@
+use crate::error_code::ErrorCode;
 use crate::keyword::Keyword;
-use crate::lexer::get_line_by_char_index;
+use crate::lexer::{get_line_by_char_index, get_position_by_char_index};
 use crate::op::Op;
 use crate::token::{TT, Token};
+use crate::node::{Arm, Catch, Node};
+use crate::integer::Integer;
+use crate::pattern::Pattern;
+use crate::range::Range;
 use crate::types::Type;
-use crate::node::Node;
 use std::io;
+use std::mem;
+use std::rc::Rc;
 
 pub struct ParserError {
     token: Token,
@@ -16,21 +22,30 @@ impl ParserError {
         Self { token, err }
     }
 
+    pub fn get_token(&self) -> &Token {
+        &self.token
+    }
+
+    pub fn get_error(&self) -> &io::Error {
+        &self.err
+    }
+
     pub fn format(&self, text: &str) -> io::Error {
         let chars: Vec<char> = text.chars().collect();
-        let start = self.token.get_start().clone();
-        let end = self.token.get_end().clone();
+        let start = *self.token.get_start();
+        let end = *self.token.get_end();
 
         let line = get_line_by_char_index(&chars, start).unwrap_or(&[]);
-        let pointers = line.into_iter().map(|_| '^').collect::<String>();
+        let (_, col) = get_position_by_char_index(&chars, start);
+        let pointers = " ".repeat(col) + &"^".repeat(end.saturating_sub(start).max(1));
 
         io::Error::new(
             self.err.kind(),
             format!(
                 "...\n{}\n{}\nParser error on \"{}\", token - {:?}: {}\n...",
-                line.into_iter().collect::<String>(),
+                line.iter().collect::<String>(),
                 pointers,
-                &text[start..end],
+                chars.get(start..end).unwrap_or(&[]).iter().collect::<String>(),
                 self.token.get_tt(),
                 self.err
             )
@@ -41,6 +56,11 @@ impl ParserError {
 pub struct Parser<'t> {
     tokens: &'t [Token],
     pos: usize,
+    functions: usize,
+    /// Which tokens are the `?` of a `cond ? a : b`.
+    conditionals: Vec<bool>,
+    loops: usize,
+    doc: Option<String>,
     ct: Option<&'t Token>
 }
 
@@ -48,7 +68,8 @@ impl<'t> Parser<'t> {
     pub fn new(tokens: &'t [Token]) -> Self {
         let pos = 0;
         let ct = tokens.get(pos);
-        Self { tokens, pos, ct }
+        let conditionals = conditional_questions(&tokens.iter().map(|token| token.get_tt()).collect::<Vec<_>>());
+        Self { tokens, pos, functions: 0, conditionals, loops: 0, doc: None, ct }
     }
 
     pub fn step(&mut self) {
@@ -59,23 +80,26 @@ impl<'t> Parser<'t> {
     pub fn atom(&mut self) -> Result<Node, ParserError> {
         let ct = match self.ct {
             Some(ct) => ct,
-            None => return Err(ParserError::new((&self.tokens[self.tokens.len()-1]).clone(), io::Error::new(
-                io::ErrorKind::InvalidInput,
+            None => return Err(ParserError::new(self.tokens[self.tokens.len()-1].clone(), ErrorCode::UnexpectedEnd.error(
                 "can\'t parse one more token, reached end"
             ))),
         };
         let tt = ct.get_tt();
 
-        if let TT::Type(Type::Int(_) | Type::Float(_)) = tt {
+        if let TT::Type(_) = tt {
             self.step();
             let number = ct.get_tt().clone().try_into().map_err(|err| ParserError::new(
                 ct.clone(),
                 err
             ))?;
-            return Ok(Node::Number(number));
+            return Ok(Node::Number(number, ct.clone()));
         } else if let TT::Indenifier(indentifier) = tt {
             self.step();
-            return Ok(Node::GetVar(indentifier.clone()));
+            return Ok(Node::GetVar(indentifier.clone(), ct.clone()));
+        } else if let TT::LSQ = tt {
+            self.step();
+            let items = self.parse_items(TT::RSQ)?;
+            return Ok(Node::List(items, ct.clone()));
         } else if let TT::LPR = tt {
             self.step();
             let expr = self.expr()?;
@@ -83,17 +107,13 @@ impl<'t> Parser<'t> {
                 self.step();
                 return Ok(expr);
             } else {                
-                return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), io::Error::new(
-                    io::ErrorKind::InvalidInput,
+                return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnclosedParenthesis.error(
                     "expected \')\'"
                 )));
             }
         }
 
-        Err(ParserError::new(ct.clone(), io::Error::new(
-            io::ErrorKind::InvalidInput,    
-            "expected int or float"
-        )))
+        Err(ParserError::new(ct.clone(), ErrorCode::ExpectedExpression.error("expected an expression")))
     }
 
     pub fn power(&mut self) -> Result<Node, ParserError> {
@@ -102,16 +122,77 @@ impl<'t> Parser<'t> {
                 TT::Op(Op::Pow) => true,
                 _ => false,
             },
-            |parser| parser.atom(),
+            |parser| parser.call(),
             |parser| parser.factor(),
         )
     }
 
+    pub fn call(&mut self) -> Result<Node, ParserError> {
+        let start = self.ct.cloned();
+        let mut node = self.atom()?;
+
+        while let Some(ct) = self.ct {
+            match ct.get_tt() {
+                TT::LPR => {
+                    self.step();
+                    let args = self.parse_arguments()?;
+                    node = Node::Call(Box::new(node), args, start.clone().unwrap_or_else(|| self.tokens[self.pos-1].clone()));
+                },
+                TT::DOT | TT::QDOT => {
+                    self.step();
+                    let name = match self.ct.map(|ct| ct.get_tt()) {
+                        Some(TT::Indenifier(name)) => name.clone(),
+                        _ => return Err(ParserError::new(self.ct.unwrap_or(ct).clone(), ErrorCode::ExpectedIdentifier.error(
+                            format!("expected member name after \'{}\'", ct.get_tt().to_string())
+                        ))),
+                    };
+                    self.step();
+
+                    let optional = matches!(ct.get_tt(), TT::QDOT);
+                    node = match self.ct.map(|ct| ct.get_tt()) {
+                        Some(TT::LPR) => {
+                            self.step();
+                            let args = self.parse_arguments()?;
+                            Node::CallMethod(Box::new(node), name, args, optional, ct.clone())
+                        },
+                        _ => Node::GetMember(Box::new(node), name, optional, ct.clone()),
+                    };
+                },
+                TT::LSQ => {
+                    self.step();
+                    let index = self.expr()?;
+
+                    match self.ct.map(|ct| ct.get_tt()) {
+                        Some(TT::RSQ) => self.step(),
+                        _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnclosedParenthesis.error(
+                            "expected \']\'"
+                        ))),
+                    }
+
+                    node = Node::Index(Box::new(node), Box::new(index), ct.clone());
+                },
+                TT::QUESTION if self.conditionals[self.pos] => break,
+                TT::QUESTION => {
+                    if self.functions == 0 {
+                        return Err(ParserError::new(ct.clone(), ErrorCode::ReturnOutsideFunction.error(
+                            "can\'t use ? outside of a function, it returns the err from one"
+                        )));
+                    }
+                    self.step();
+
+                    node = Node::Unwrap(Box::new(node), ct.clone());
+                },
+                _ => break
+            }
+        }
+
+        Ok(node)
+    }
+
     pub fn factor(&mut self) -> Result<Node, ParserError> {
         let ct = match self.ct {
             Some(ct) => ct,
-            None => return Err(ParserError::new((&self.tokens[self.tokens.len()-1]).clone(), io::Error::new(
-                io::ErrorKind::InvalidInput,
+            None => return Err(ParserError::new(self.tokens[self.tokens.len()-1].clone(), ErrorCode::UnexpectedEnd.error(
                 "can\'t parse one more token, reached end"
             ))),
         };
@@ -119,7 +200,11 @@ impl<'t> Parser<'t> {
         if let TT::Op(Op::Sub) = ct.get_tt() {
             self.step();
             let factor = self.factor()?;
-            return Ok(Node::UnaryOp(Box::new(factor)));
+            return Ok(Node::UnaryOp(Box::new(factor), ct.clone()));
+        } else if let TT::Op(Op::Xor) = ct.get_tt() {
+            self.step();
+            let factor = self.factor()?;
+            return Ok(Node::BitNotOp(Box::new(factor), ct.clone()));
         }
 
         self.power()
@@ -127,23 +212,90 @@ impl<'t> Parser<'t> {
 
     pub fn term(&mut self) -> Result<Node, ParserError> {
         self.bin_op_same(|token| match token {
-            TT::Op(Op::Devd | Op::Mult | Op::Remain) => true,
+            TT::Op(Op::Devd | Op::FloorDevd | Op::Mult | Op::Remain) => true,
             _ => false,
         }, |parser| parser.factor())
     }
 
     pub fn logic_expr(&mut self) -> Result<Node, ParserError> {
-        if let Some(TT::Op(Op::Not)) = self.ct.map(|ct| ct.get_tt()) {
+        if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Op(Op::Not))) {
             self.step();
 
             let node = self.logic_expr()?;
 
-            return Ok(Node::NotOp(Box::new(node)));
+            return Ok(Node::NotOp(Box::new(node), ct.clone()));
         }
 
-        self.bin_op_same(|token| match token {
+        let node = self.bin_op_same(|token| match token {
             TT::Op(type_) => type_.logical(),
             _ => false,
+        }, |parser| parser.range_expr())?;
+
+        match self.ct {
+            Some(ct) if matches!(ct.get_tt(), TT::Keyword(Keyword::Is)) => {
+                self.step();
+                match self.ct.map(|ct| ct.get_tt()) {
+                    Some(TT::Type(Type::None)) => self.step(),
+                    _ => return Err(ParserError::new(self.ct.unwrap_or(ct).clone(), ErrorCode::UnexpectedToken.error(
+                        "expected none after is"
+                    ))),
+                }
+
+                Ok(Node::IsNone(Box::new(node)))
+            },
+            _ => Ok(node)
+        }
+    }
+
+    /// `a..b`, `a..=b` and either of them followed by `step s`.
+    pub fn range_expr(&mut self) -> Result<Node, ParserError> {
+        let start = self.bit_or_expr()?;
+
+        let (ct, inclusive) = match self.ct {
+            Some(ct) if matches!(ct.get_tt(), TT::DOTDOT) => (ct, false),
+            Some(ct) if matches!(ct.get_tt(), TT::DOTDOTEQ) => (ct, true),
+            _ => return Ok(start),
+        };
+        self.step();
+        self.skip_new_lines();
+        let end = self.bit_or_expr()?;
+
+        let step = match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::Keyword(Keyword::Step)) => {
+                self.step();
+                Some(Box::new(self.bit_or_expr()?))
+            },
+            _ => None
+        };
+
+        Ok(Node::Range(Box::new(start), Box::new(end), step, inclusive, ct.clone()))
+    }
+
+    pub fn bit_or_expr(&mut self) -> Result<Node, ParserError> {
+        self.bin_op_same(|token| match token {
+            TT::Op(Op::BitOr) => true,
+            _ => false,
+        }, |parser| parser.bit_xor_expr())
+    }
+
+    pub fn bit_xor_expr(&mut self) -> Result<Node, ParserError> {
+        self.bin_op_same(|token| match token {
+            TT::Op(Op::Xor) => true,
+            _ => false,
+        }, |parser| parser.bit_and_expr())
+    }
+
+    pub fn bit_and_expr(&mut self) -> Result<Node, ParserError> {
+        self.bin_op_same(|token| match token {
+            TT::Op(Op::BitAnd) => true,
+            _ => false,
+        }, |parser| parser.shift_expr())
+    }
+
+    pub fn shift_expr(&mut self) -> Result<Node, ParserError> {
+        self.bin_op_same(|token| match token {
+            TT::Op(Op::Shl | Op::Shr) => true,
+            _ => false,
         }, |parser| parser.arithm_expr())
     }
 
@@ -157,38 +309,198 @@ impl<'t> Parser<'t> {
     pub fn expr(&mut self) -> Result<Node, ParserError> {
         if let Some(TT::Keyword(Keyword::Let)) = self.ct.map(|ct| ct.get_tt()) {
             self.step();
-            let (name, value) = self.parse_variable()?;
+            let (token, name, value) = self.parse_variable()?;
+
+            return Ok(Node::DeclareVar(name, Box::new(value), token));
+        } else if let Some(TT::Keyword(Keyword::Fn)) = self.ct.map(|ct| ct.get_tt()) {
+            let doc = self.doc.take();
+            self.step();
+            let (token, name, params, body) = self.parse_function()?;
+
+            return Ok(Node::DeclareFn(name, params, Rc::new(body), doc, token));
+        } else if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Keyword(Keyword::Return))) {
+            if self.functions == 0 {
+                return Err(ParserError::new(ct.clone(), ErrorCode::ReturnOutsideFunction.error(
+                    "can\'t return outside of a function"
+                )));
+            }
+
+            self.step();
+            let value = match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::NL | TT::SEMI | TT::RBR | TT::EOF) | None => None,
+                _ => Some(Box::new(self.expr()?)),
+            };
+
+            return Ok(Node::Return(value, ct.clone()));
+        } else if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Keyword(Keyword::Throw))) {
+            self.step();
+            let value = self.expr()?;
+
+            return Ok(Node::Throw(Box::new(value), ct.clone()));
+        } else if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Keyword(Keyword::Try))) {
+            self.step();
+            let (body, catches, finally) = self.parse_try()?;
 
-            return Ok(Node::DeclareVar(name, Box::new(value)));
+            return Ok(Node::Try(Box::new(body), catches, finally.map(Box::new), ct.clone()));
+        } else if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Keyword(Keyword::Match))) {
+            self.step();
+            let value = self.coalesce_expr()?;
+            let arms = self.parse_arms()?;
+
+            return Ok(Node::Match(Box::new(value), arms, ct.clone()));
+        } else if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Keyword(Keyword::If))) {
+            self.step();
+            return self.parse_if(ct);
+        } else if let Some(TT::Keyword(Keyword::For)) = self.ct.map(|ct| ct.get_tt()) {
+            self.step();
+            let (token, name, iterable, body) = self.parse_for()?;
+
+            return Ok(Node::For(name, Box::new(iterable), Box::new(body), token));
+        } else if let Some(ct) = self.ct.filter(|ct| matches!(ct.get_tt(), TT::Keyword(Keyword::Break | Keyword::Continue))) {
+            if self.loops == 0 {
+                return Err(ParserError::new(ct.clone(), ErrorCode::BreakOutsideLoop.error(
+                    format!("can\'t {} outside of a loop", ct.get_tt().to_string())
+                )));
+            }
+            self.step();
+
+            return Ok(match ct.get_tt() {
+                TT::Keyword(Keyword::Break) => Node::Break(ct.clone()),
+                _ => Node::Continue(ct.clone()),
+            });
         }
 
-        self.bin_op_same(|token| match token {
-            TT::Op(Op::And | Op::Or) => true,
+        self.conditional_expr()
+    }
+
+    /// `cond ? a : b`, it binds looser than `??` and `||` and nests to the right.
+    pub fn conditional_expr(&mut self) -> Result<Node, ParserError> {
+        let start = self.ct.cloned();
+        let condition = self.coalesce_expr()?;
+
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::QUESTION) => self.step(),
+            _ => return Ok(condition),
+        }
+        self.skip_new_lines();
+        let then = self.expr()?;
+
+        self.skip_new_lines();
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::COLON) => self.step(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnexpectedToken.error(
+                "expected \':\' after the first branch of ?"
+            ))),
+        }
+        self.skip_new_lines();
+        let otherwise = self.expr()?;
+
+        let token = start.unwrap_or_else(|| self.tokens[self.pos-1].clone());
+        Ok(Node::If(Box::new(condition), Box::new(then), Some(Box::new(otherwise)), token))
+    }
+
+    pub fn coalesce_expr(&mut self) -> Result<Node, ParserError> {
+        self.short_circuit(|token| match token {
+            TT::Op(Op::Coalesce) => true,
+            _ => false,
+        }, |parser| parser.or_expr())
+    }
+
+    pub fn or_expr(&mut self) -> Result<Node, ParserError> {
+        self.short_circuit(|token| match token {
+            TT::Op(Op::Or) => true,
+            _ => false,
+        }, |parser| parser.and_expr())
+    }
+
+    pub fn and_expr(&mut self) -> Result<Node, ParserError> {
+        self.short_circuit(|token| match token {
+            TT::Op(Op::And) => true,
             _ => false,
         }, |parser| parser.logic_expr())
     }
 
-    pub fn parse(&mut self) -> Result<Node, ParserError> {
-        let mut result = self.expr();
+    pub fn statements(&mut self) -> Result<Node, ParserError> {
+        let mut statements = Vec::new();
+
+        loop {
+            self.skip_separators();
+
+            if let Some(TT::EOF | TT::RBR) | None = self.ct.map(|ct| ct.get_tt()) {
+                self.doc = None;
+                break
+            }
+
+            statements.push(self.expr()?);
+            // doc comments only document a `fn` right below them
+            self.doc = None;
 
-        if result.is_ok() {
             match self.ct.map(|ct| ct.get_tt()) {
-                Some(TT::EOF) => {},
-                _ => result = Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.tokens.len()-1]).clone(), io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "expected \'+\', \'-\', \'/\', \'*\', \'^\' or \'%\'"
+                Some(TT::NL | TT::SEMI | TT::EOF | TT::RBR) => {},
+                _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.tokens.len()-1]).clone(), ErrorCode::UnexpectedToken.error(
+                    "expected an operator, \';\' or new line"
                 )))
             }
         }
 
-        result
+        Ok(Node::Statements(statements))
     }
 
-    fn parse_variable(&mut self) -> Result<(String, Node), ParserError> {
-        let indentifier = match self.ct.map(|ct| ct.get_tt()) {
-            Some(TT::Indenifier(indenifier)) => indenifier,
-            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), io::Error::new(
-                io::ErrorKind::InvalidInput,
+    pub fn block(&mut self) -> Result<Node, ParserError> {
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::LBR) => self.step(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnexpectedToken.error(
+                "expected \'{\'"
+            ))),
+        }
+
+        let statements = self.statements()?;
+
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::RBR) => self.step(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnclosedBrace.error(
+                "expected \'}\'"
+            ))),
+        }
+
+        Ok(statements)
+    }
+
+    pub fn parse(&mut self) -> Result<Node, ParserError> {
+        let statements = self.statements()?;
+
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::EOF) => Ok(statements),
+            _ => Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.tokens.len()-1]).clone(), ErrorCode::UnexpectedToken.error(
+                "unexpected \'}\'"
+            )))
+        }
+    }
+
+    /// Also collects the doc comments between statements for the next `fn`.
+    fn skip_separators(&mut self) {
+        while let Some(TT::NL | TT::SEMI | TT::Doc(_)) = self.ct.map(|ct| ct.get_tt()) {
+            if let Some(TT::Doc(line)) = self.ct.map(|ct| ct.get_tt()) {
+                self.doc = Some(match self.doc.take() {
+                    Some(doc) => doc + "\n" + line,
+                    None => line.clone(),
+                });
+            }
+
+            self.step()
+        }
+    }
+
+    fn skip_new_lines(&mut self) {
+        while let Some(TT::NL) = self.ct.map(|ct| ct.get_tt()) {
+            self.step()
+        }
+    }
+
+    fn parse_variable(&mut self) -> Result<(Token, String, Node), ParserError> {
+        let (token, indentifier) = match self.ct.map(|ct| (ct, ct.get_tt())) {
+            Some((token, TT::Indenifier(indenifier))) => (token, indenifier),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedIdentifier.error(
                 "expected indentifier"
             ))),
         };
@@ -197,16 +509,343 @@ impl<'t> Parser<'t> {
 
         match self.ct.map(|ct| ct.get_tt()) {
             Some(TT::EQ) => {},
-            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), io::Error::new(
-                io::ErrorKind::InvalidInput,
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedAssignment.error(
                 "expected EQ"
             ))),
         }
 
+        // like after a binary operator, the value may start on the next line
         self.step();
+        self.skip_new_lines();
         let expr = self.expr()?;
         
-        Ok((indentifier.clone(), expr))
+        Ok((token.clone(), indentifier.clone(), expr))
+    }
+
+    fn parse_function(&mut self) -> Result<(Token, String, Vec<String>, Node), ParserError> {
+        let (token, name) = match self.ct.map(|ct| (ct, ct.get_tt())) {
+            Some((token, TT::Indenifier(indenifier))) => (token, indenifier),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedIdentifier.error(
+                "expected function name"
+            ))),
+        };
+
+        self.step();
+
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::LPR) => self.step(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnexpectedToken.error(
+                "expected \'(\'"
+            ))),
+        }
+
+        let mut params = Vec::new();
+        loop {
+            match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::RPR) => break,
+                Some(TT::Indenifier(param)) => params.push(param.clone()),
+                _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedIdentifier.error(
+                    "expected parameter name"
+                ))),
+            }
+
+            self.step();
+
+            match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::COMMA) => self.step(),
+                Some(TT::RPR) => break,
+                _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnclosedParenthesis.error(
+                    "expected \',\' or \')\'"
+                ))),
+            }
+        }
+
+        self.step();
+
+        // a loop around the declaration doesn't continue into the body
+        let loops = mem::replace(&mut self.loops, 0);
+        self.functions += 1;
+        let body = self.block();
+        self.functions -= 1;
+        self.loops = loops;
+
+        Ok((token.clone(), name.clone(), params, body?))
+    }
+
+    fn parse_try(&mut self) -> Result<(Node, Vec<Catch>, Option<Node>), ParserError> {
+        let body = self.block()?;
+
+        let mut catches = Vec::new();
+        while let Some(TT::Keyword(Keyword::Catch)) = self.peek_past_new_lines() {
+            self.skip_new_lines();
+            self.step();
+
+            let (kinds, (name, token)) = self.parse_catch_names()?;
+            catches.push(Catch::new(kinds, name, self.block()?, token));
+        }
+
+        let finally = match self.peek_past_new_lines() {
+            Some(TT::Keyword(Keyword::Finally)) => {
+                self.skip_new_lines();
+                self.step();
+                Some(self.block()?)
+            },
+            _ => None
+        };
+
+        if catches.is_empty() && finally.is_none() {
+            return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedCatch.error(
+                "expected catch or finally after try block"
+            )));
+        }
+
+        Ok((body, catches, finally))
+    }
+
+    /// Parses `Kind | OtherKind name` or just `name` after `catch`, the name comes with its token.
+    fn parse_catch_names(&mut self) -> Result<(Vec<String>, (String, Token)), ParserError> {
+        let mut names = vec![self.parse_catch_name()?];
+        while let Some(TT::Op(Op::BitOr)) = self.ct.map(|ct| ct.get_tt()) {
+            self.step();
+            names.push(self.parse_catch_name()?);
+        }
+
+        if let Some(TT::Indenifier(_)) = self.ct.map(|ct| ct.get_tt()) {
+            let name = self.parse_catch_name()?;
+            return Ok((names.into_iter().map(|(kind, _)| kind).collect(), name));
+        }
+
+        match names.pop() {
+            Some(name) if names.is_empty() => Ok((Vec::new(), name)),
+            _ => Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedIdentifier.error(
+                "expected a name for the caught error"
+            ))),
+        }
+    }
+
+    fn parse_catch_name(&mut self) -> Result<(String, Token), ParserError> {
+        match self.ct.map(|ct| (ct, ct.get_tt())) {
+            Some((ct, TT::Indenifier(name))) => {
+                self.step();
+                Ok((name.clone(), ct.clone()))
+            },
+            _ => Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedIdentifier.error(
+                "expected error kind or name after catch"
+            ))),
+        }
+    }
+
+    /// Parses what follows `if`, an `else if` chain becomes nested [`Node::If`]s.
+    fn parse_if(&mut self, token: &Token) -> Result<Node, ParserError> {
+        let condition = self.coalesce_expr()?;
+        let then = self.block()?;
+
+        let otherwise = match self.peek_past_new_lines() {
+            Some(TT::Keyword(Keyword::Else)) => {
+                self.skip_new_lines();
+                self.step();
+
+                match self.ct {
+                    Some(ct) if matches!(ct.get_tt(), TT::Keyword(Keyword::If)) => {
+                        self.step();
+                        Some(self.parse_if(ct)?)
+                    },
+                    _ => Some(self.block()?),
+                }
+            },
+            _ => None
+        };
+
+        Ok(Node::If(Box::new(condition), Box::new(then), otherwise.map(Box::new), token.clone()))
+    }
+
+    fn parse_arms(&mut self) -> Result<Vec<Arm>, ParserError> {
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::LBR) => self.step(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnexpectedToken.error(
+                "expected \'{\' after the matched value"
+            ))),
+        }
+
+        let mut arms = Vec::new();
+        loop {
+            while let Some(TT::NL | TT::COMMA) = self.ct.map(|ct| ct.get_tt()) {
+                self.step()
+            }
+
+            if let Some(TT::RBR) = self.ct.map(|ct| ct.get_tt()) {
+                self.step();
+                break
+            }
+
+            let pattern = self.parse_pattern()?;
+
+            match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::ARROW) => self.step(),
+                _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnexpectedToken.error(
+                    "expected \'=>\' after the pattern"
+                ))),
+            }
+
+            let body = match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::LBR) => self.block()?,
+                _ => self.expr()?,
+            };
+            arms.push(Arm::new(pattern, body));
+
+            match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::NL | TT::COMMA | TT::RBR) => {},
+                _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.tokens.len()-1]).clone(), ErrorCode::UnclosedBrace.error(
+                    "expected \',\', new line or \'}\' after the arm"
+                ))),
+            }
+        }
+
+        Ok(arms)
+    }
+
+    /// `_`, a name, a literal, an int range, or `ok(...)` and `err(...)` around another pattern.
+    fn parse_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let ct = match self.ct {
+            Some(ct) => ct,
+            None => return Err(ParserError::new(self.tokens[self.tokens.len()-1].clone(), ErrorCode::UnexpectedEnd.error(
+                "expected a pattern, reached end"
+            ))),
+        };
+
+        if let TT::Type(_) | TT::Op(Op::Sub) = ct.get_tt() {
+            return self.parse_value_pattern();
+        }
+        self.step();
+
+        match ct.get_tt() {
+            TT::Indenifier(name) if name == "_" => Ok(Pattern::Wildcard),
+            TT::Indenifier(name) if (name == "ok" || name == "err") && matches!(self.ct.map(|ct| ct.get_tt()), Some(TT::LPR)) => {
+                self.step();
+                let inner = Box::new(self.parse_pattern()?);
+
+                match self.ct.map(|ct| ct.get_tt()) {
+                    Some(TT::RPR) => self.step(),
+                    _ => return Err(ParserError::new(self.ct.unwrap_or(ct).clone(), ErrorCode::UnclosedParenthesis.error(
+                        "expected \')\'"
+                    ))),
+                }
+
+                Ok(if name == "ok" { Pattern::Ok(inner) } else { Pattern::Err(inner) })
+            },
+            TT::Indenifier(name) => Ok(Pattern::Bind(name.clone(), ct.clone())),
+            _ => Err(ParserError::new(ct.clone(), ErrorCode::UnexpectedToken.error(
+                "expected a pattern: _, a name, a literal, a range, ok(...) or err(...)"
+            ))),
+        }
+    }
+
+    /// A literal pattern like `-1` or `"text"`, or a range of int literals like `0..10`.
+    fn parse_value_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let start = self.parse_literal()?;
+
+        let (ct, inclusive) = match self.ct {
+            Some(ct) if matches!(ct.get_tt(), TT::DOTDOT) => (ct, false),
+            Some(ct) if matches!(ct.get_tt(), TT::DOTDOTEQ) => (ct, true),
+            _ => return Ok(Pattern::Value(start)),
+        };
+        self.step();
+        let end = self.parse_literal()?;
+
+        match (start, end) {
+            (Type::Int(start), Type::Int(end)) => Range::new(start, end, Integer::from(1), inclusive).map(Pattern::Range).ok_or_else(
+                || ParserError::new(ct.clone(), ErrorCode::ZeroStep.error("can\'t make a range with step 0"))
+            ),
+            (start, end) => Err(ParserError::new(ct.clone(), ErrorCode::TypeMismatch.error(format!(
+                "can\'t match a range of {} and {}, both bounds must be ints", start.to_string(), end.to_string()
+            )))),
+        }
+    }
+
+    /// A value token, numbers may be negated with `-`.
+    fn parse_literal(&mut self) -> Result<Type, ParserError> {
+        let ct = self.ct.unwrap_or(&self.tokens[self.tokens.len()-1]);
+        let negative = matches!(ct.get_tt(), TT::Op(Op::Sub));
+        if negative {
+            self.step();
+        }
+
+        let value = match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::Type(value)) => value.clone(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(ct).clone(), ErrorCode::ExpectedExpression.error(
+                "expected a literal in the pattern"
+            ))),
+        };
+        self.step();
+
+        match negative {
+            true => value.inverted().map_err(|err| ParserError::new(ct.clone(), err)),
+            false => Ok(value),
+        }
+    }
+
+    /// Whether the token `offset` places ahead can begin an expression.
+    /// The first token that isn't a new line, without moving past anything.
+    fn peek_past_new_lines(&self) -> Option<&TT> {
+        self.tokens[self.pos.min(self.tokens.len())..].iter()
+            .map(|token| token.get_tt())
+            .find(|tt| !matches!(tt, TT::NL))
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Node>, ParserError> {
+        self.parse_items(TT::RPR)
+    }
+
+    /// Comma separated expressions up to and including `close`.
+    fn parse_items(&mut self, close: TT) -> Result<Vec<Node>, ParserError> {
+        let is_close = |ct: Option<&Token>| ct.is_some_and(|ct| mem::discriminant(ct.get_tt()) == mem::discriminant(&close));
+        let mut items = Vec::new();
+
+        loop {
+            if is_close(self.ct) {
+                break
+            }
+
+            items.push(self.expr()?);
+
+            match self.ct.map(|ct| ct.get_tt()) {
+                Some(TT::COMMA) => self.step(),
+                _ if is_close(self.ct) => break,
+                _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnclosedParenthesis.error(
+                    format!("expected \',\' or \'{}\'", close.to_string())
+                ))),
+            }
+        }
+
+        self.step();
+
+        Ok(items)
+    }
+
+    fn parse_for(&mut self) -> Result<(Token, String, Node, Node), ParserError> {
+        let (token, name) = match self.ct.map(|ct| (ct, ct.get_tt())) {
+            Some((token, TT::Indenifier(indenifier))) => (token, indenifier),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::ExpectedIdentifier.error(
+                "expected loop variable name"
+            ))),
+        };
+
+        self.step();
+
+        match self.ct.map(|ct| ct.get_tt()) {
+            Some(TT::Op(Op::In)) => self.step(),
+            _ => return Err(ParserError::new(self.ct.unwrap_or(&self.tokens[self.pos-1]).clone(), ErrorCode::UnexpectedToken.error(
+                "expected in after the loop variable"
+            ))),
+        }
+
+        let iterable = self.coalesce_expr()?;
+
+        self.loops += 1;
+        let body = self.block();
+        self.loops -= 1;
+
+        Ok((token.clone(), name.clone(), iterable, body?))
     }
 
     fn bin_op<W, FA, FB>(&mut self, mut wl: W, mut func_a: FA, mut func_b: FB) -> Result<Node, ParserError>
@@ -219,11 +858,34 @@ impl<'t> Parser<'t> {
             }
 
             self.step();
+            self.skip_new_lines();
             let right = func_b(self)?;
             left = Node::BinOp(Box::new(left), ct.get_tt().clone().try_into().map_err(|err| ParserError::new(
                 ct.clone(),
                 err
-            ))?, Box::new(right))
+            ))?, Box::new(right), ct.clone())
+        }
+
+        Ok(left)
+    }
+
+    /// Like [`Self::bin_op_same`] but builds [`Node::LogicOp`], which evaluates the right operand only when needed.
+    fn short_circuit<W, F>(&mut self, mut wl: W, mut func: F) -> Result<Node, ParserError>
+        where W: FnMut(&TT) -> bool, F: FnMut(&mut Self) -> Result<Node, ParserError> {
+        let mut left = func(self)?;
+
+        while let Some(ct) = self.ct {
+            if !wl(ct.get_tt()) {
+                break
+            }
+
+            self.step();
+            self.skip_new_lines();
+            let right = func(self)?;
+            left = Node::LogicOp(Box::new(left), ct.get_tt().clone().try_into().map_err(|err| ParserError::new(
+                ct.clone(),
+                err
+            ))?, Box::new(right), ct.clone())
         }
 
         Ok(left)
@@ -239,13 +901,170 @@ impl<'t> Parser<'t> {
             }
 
             self.step();
+            self.skip_new_lines();
             let right = func(self)?;
             left = Node::BinOp(Box::new(left), ct.get_tt().clone().try_into().map_err(|err| ParserError::new(
                 ct.clone(),
                 err
-            ))?, Box::new(right))
+            ))?, Box::new(right), ct.clone())
         }
 
         Ok(left)
     }
+}
+
+/// Whether an expression can start with `tt`.
+fn starts_expression(tt: &TT) -> bool {
+    match tt {
+        TT::Type(_) | TT::Indenifier(_) | TT::LPR | TT::LSQ | TT::Op(Op::Sub | Op::Not | Op::Xor) => true,
+        TT::Keyword(keyword) => matches!(keyword, Keyword::If | Keyword::Match | Keyword::Try),
+        _ => false
+    }
+}
+
+/// For every token, whether it's a `?` starting a `cond ? a : b` rather than unwrapping a result.
+///
+/// A `?` followed by an expression is a conditional, unless that expression starts with `-` or `~`: `r? - 1`
+/// and `r ? -1 : 0` only differ in the `:`. Such a `?`, or one at the end of a line, is a conditional when the
+/// rest of the statement has a `:` for it that isn't taken by a conditional around it or a later `?`.
+pub fn conditional_questions(tts: &[&TT]) -> Vec<bool> {
+    let mut conditional = vec![false; tts.len()];
+    // conditionals waiting for their `:`, one count for each level of brackets
+    let mut open = vec![0usize];
+
+    for (index, tt) in tts.iter().enumerate() {
+        match tt {
+            TT::LPR | TT::LSQ | TT::LBR => open.push(0),
+            TT::RPR | TT::RSQ | TT::RBR if open.len() > 1 => {
+                open.pop();
+            },
+            _ => {},
+        }
+
+        let waiting = open.last_mut().expect("the top level is never closed");
+        match tt {
+            TT::COLON => *waiting = waiting.saturating_sub(1),
+            TT::NL | TT::SEMI if ends_statement(tts, index) => *waiting = 0,
+            TT::QUESTION => {
+                conditional[index] = match tts.get(index + 1) {
+                    Some(TT::Op(Op::Sub | Op::Xor) | TT::NL) => free_colons(tts, index) > *waiting,
+                    Some(next) => starts_expression(next),
+                    None => false,
+                };
+                *waiting += conditional[index] as usize
+            },
+            _ => {},
+        }
+    }
+
+    conditional
+}
+
+/// Whether the new line or `;` at `index` ends a statement, a conditional goes on over new lines next to
+/// its `?` and `:` when the next line can go on with an expression.
+fn ends_statement(tts: &[&TT], index: usize) -> bool {
+    if matches!(tts[index], TT::SEMI) {
+        return true;
+    }
+
+    let next = tts[index..].iter().find(|tt| !matches!(tt, TT::NL));
+    let after_branch = matches!(index.checked_sub(1).map(|prev| tts[prev]), Some(TT::QUESTION | TT::COLON));
+    let goes_on = after_branch && next.is_some_and(|next| starts_expression(next));
+    !goes_on && !matches!(next, Some(TT::COLON))
+}
+
+/// `:`s after the `?` at `index` in the rest of its statement and brackets, less the ones later `?`s that are
+/// conditionals for sure take, so the first of several ambiguous `?`s gets a `:` first.
+fn free_colons(tts: &[&TT], index: usize) -> usize {
+    let (mut depth, mut colons, mut questions) = (0usize, 0usize, 0usize);
+
+    for (index, tt) in tts.iter().enumerate().skip(index + 1) {
+        match tt {
+            TT::LPR | TT::LSQ | TT::LBR => depth += 1,
+            TT::RPR | TT::RSQ | TT::RBR if depth == 0 => break,
+            TT::RPR | TT::RSQ | TT::RBR => depth -= 1,
+            _ if depth > 0 => {},
+            TT::QUESTION if tts.get(index + 1).is_some_and(|next| starts_expression(next) && !matches!(next, TT::Op(Op::Sub | Op::Xor))) => {
+                questions += 1
+            },
+            TT::COLON => colons += 1,
+            TT::NL | TT::SEMI if ends_statement(tts, index) => break,
+            TT::COMMA | TT::EOF => break,
+            _ => {},
+        }
+    }
+
+    colons.saturating_sub(questions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parser;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+
+    /// The value of `body` as the body of `fn f(x)` called with 3, so `?` can return from it.
+    fn eval(body: &str) -> String {
+        let text = format!("fn f(x) {{\n{}\n}}\nf(3)", body);
+        match Interpreter::new("<test>", &text).and_then(|mut interpreter| interpreter.run()) {
+            Ok(value) => format!("{:?}", value),
+            Err(err) => panic!("{} failed: {}", text, err),
+        }
+    }
+
+    #[test]
+    fn postfix_question_before_minus() {
+        assert_eq!(eval("let y = ok(x)? - 1\ny"), "int(2)");
+        assert_eq!(eval("ok(~x)? - 1"), "int(-5)");
+        assert_eq!(eval("(ok(5)? - 1)"), "int(4)");
+        assert_eq!(eval("let u = ok(!true)?\nu"), "bool(false)");
+        assert_eq!(eval("ok(x)? - 1 > 0 ? 10 : 20"), "int(10)");
+    }
+
+    #[test]
+    fn conditional_before_minus() {
+        assert_eq!(eval("x > 0 ? -1 : 1"), "int(-1)");
+        assert_eq!(eval("x > 0 ? ~x : 0"), "int(-4)");
+        assert_eq!(eval("[x > 1 ? -1 : 0][0]"), "int(-1)");
+        assert_eq!(eval("x > 5 ? -1 : x < 0 ? -2 : 0"), "int(0)");
+    }
+
+    #[test]
+    fn conditionals_around_postfix_question() {
+        assert_eq!(eval("x > 0 ? ok(1)? - 1\n    : 5"), "int(0)");
+        assert_eq!(eval("x > 0 ?\n    ok(2)? - 1 :\n    5"), "int(1)");
+        assert_eq!(eval("x == 3 ? ok(1)? : 0"), "int(1)");
+        assert_eq!(eval("x > 9 ? 1 : x > 1 ? 2 : 3"), "int(2)");
+    }
+
+    #[test]
+    fn let_value_on_the_next_line() {
+        assert_eq!(eval("let y =\n    x + 1\ny"), "int(4)");
+        assert_eq!(eval("let y =\n\n    x > 2 ? 1 : 2\ny"), "int(1)");
+    }
+
+    #[test]
+    fn unexpected_tokens_after_a_statement_are_pointed_at() {
+        let text = "let s = 1\ns = s + 1";
+        let tokens = Lexer::new(text).tokenize().expect("the test program lexes");
+        let err = match Parser::new(&tokens).parse() {
+            Ok(_) => panic!("{} parsed", text),
+            Err(err) => err,
+        };
+
+        assert_eq!(*err.get_token().get_start(), text.rfind('=').unwrap());
+        assert!(err.format(text).to_string().contains("s = s + 1\n  ^\n"), "{}", err.format(text));
+        assert!(err.get_error().to_string().contains("[K0008] expected an operator, \';\' or new line"), "{}", err.get_error());
+    }
+
+    #[test]
+    fn missing_operands_are_reported_as_missing_expressions() {
+        for text in ["1 + ]", "let x = )"] {
+            let tokens = Lexer::new(text).tokenize().expect("the test program lexes");
+            match Parser::new(&tokens).parse() {
+                Ok(_) => panic!("{} parsed", text),
+                Err(err) => assert!(err.get_error().to_string().contains("[K0006] expected an expression"), "{}: {}", text, err.get_error()),
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use crate::range::Range;
+use crate::token::Token;
+use crate::types::Type;
+
+/// Left side of a `match` arm.
+#[derive(Clone)]
+pub enum Pattern {
+    /// `_` accepts any value without binding it.
+    Wildcard,
+    /// A name accepts any value and binds it for the arm's body.
+    Bind(String, Token),
+    /// A literal like `1`, `"text"` or `none` accepts values equal to it.
+    Value(Type),
+    /// `1..10` or `1..=9` accepts numbers equal to one of its ints.
+    Range(Range),
+    Ok(Box<Pattern>),
+    Err(Box<Pattern>)
+}
+
+impl Pattern {
+    /// Whether `value` fits the pattern, the names it binds are pushed to `bindings`.
+    pub fn matches(&self, value: &Type, bindings: &mut Vec<(String, Type)>) -> bool {
+        match (self, value) {
+            (Pattern::Wildcard, _) => true,
+            (Pattern::Bind(name, _), _) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            },
+            (Pattern::Value(expected), _) => expected.equals(value),
+            (Pattern::Range(range), _) => value.as_integer().is_some_and(|value| range.contains(&value)),
+            (Pattern::Ok(inner), Type::Ok(value)) | (Pattern::Err(inner), Type::Err(value)) => inner.matches(value, bindings),
+            _ => false
+        }
+    }
+
+    /// Names bound by the pattern, with the tokens they're written at.
+    pub fn get_names(&self) -> Vec<(&str, &Token)> {
+        match self {
+            Pattern::Bind(name, token) => vec![(name, token)],
+            Pattern::Ok(inner) | Pattern::Err(inner) => inner.get_names(),
+            Pattern::Wildcard | Pattern::Value(_) | Pattern::Range(_) => Vec::new(),
+        }
+    }
+}
+
+impl ToString for Pattern {
+    fn to_string(&self) -> String {
+        match self {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Bind(name, _) => name.clone(),
+            Pattern::Value(value) => format!("{:?}", value),
+            Pattern::Range(range) => range.to_string(),
+            Pattern::Ok(inner) => format!("ok({})", inner.to_string()),
+            Pattern::Err(inner) => format!("err({})", inner.to_string()),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::mem;
+
+use crate::integer::Integer;
+
+/// Ints from `start` towards `end` by `step`, `end` itself is part of the range only for `..=`.
+///
+/// The elements are never stored, [`Self::iter`] computes them one at a time, so `0..1_000_000_000`
+/// costs as much as `0..1` until it's iterated.
+#[derive(Clone)]
+pub struct Range {
+    start: Integer,
+    end: Integer,
+    step: Integer,
+    inclusive: bool
+}
+
+impl Range {
+    /// `None` when `step` is zero, such a range would never reach its end.
+    pub fn new(start: Integer, end: Integer, step: Integer, inclusive: bool) -> Option<Self> {
+        if step.is_zero() {
+            return None;
+        }
+
+        Some(Self { start, end, step, inclusive })
+    }
+
+    /// Number of elements, zero when `step` points away from `end`.
+    pub fn len(&self) -> Integer {
+        let distance = if self.step.is_negative() { &self.start - &self.end } else { &self.end - &self.start };
+        let step = self.step.abs();
+        let zero = Integer::from(0);
+
+        let len = match distance.cmp(&zero) {
+            Ordering::Less => None,
+            Ordering::Equal if !self.inclusive => None,
+            _ if self.inclusive => distance.checked_div(&step).map(|len| &len + &Integer::from(1)),
+            _ => (&distance + &(&step - &Integer::from(1))).checked_div(&step),
+        };
+
+        len.unwrap_or(zero)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len().is_zero()
+    }
+
+    pub fn contains(&self, value: &Integer) -> bool {
+        match (value - &self.start).checked_div_rem(&self.step) {
+            Some((index, remainder)) => remainder.is_zero() && !index.is_negative() && index < self.len(),
+            None => false,
+        }
+    }
+
+    /// The same elements in the opposite order, `None` if the last one is too large to compute.
+    pub fn rev(&self) -> Option<Self> {
+        let step = -&self.step;
+        if self.is_empty() {
+            return Some(Self { start: self.start.clone(), end: self.start.clone(), step, inclusive: false });
+        }
+
+        let last = &self.start + &(&self.len() - &Integer::from(1)).checked_mul(&self.step)?;
+        Some(Self { start: last, end: self.start.clone(), step, inclusive: true })
+    }
+
+    pub fn iter(&self) -> RangeIter {
+        RangeIter { next: self.start.clone(), step: self.step.clone(), remaining: self.len() }
+    }
+}
+
+/// Ranges are equal when they produce the same elements, so `0..3 == 0..=2`.
+impl PartialEq for Range {
+    fn eq(&self, other: &Self) -> bool {
+        let len = self.len();
+        if len != other.len() {
+            return false;
+        }
+
+        len.is_zero() || (self.start == other.start && (len == Integer::from(1) || self.step == other.step))
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.start, if self.inclusive { "..=" } else { ".." }, self.end)?;
+
+        if self.step != Integer::from(1) {
+            write!(f, " step {}", self.step)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct RangeIter {
+    next: Integer,
+    step: Integer,
+    remaining: Integer
+}
+
+impl Iterator for RangeIter {
+    type Item = Integer;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_zero() {
+            return None;
+        }
+
+        self.remaining = &self.remaining - &Integer::from(1);
+        let next = &self.next + &self.step;
+
+        Some(mem::replace(&mut self.next, next))
+    }
+}
\ No newline at end of file
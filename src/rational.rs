@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+use crate::integer::Integer;
+
+/// Exact fraction, always reduced and with a positive denominator.
+///
+/// Operations return `None` when the result would be too large for an [`Integer`] or on division by zero.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Rational {
+    numerator: Integer,
+    denominator: Integer
+}
+
+impl Rational {
+    /// `None` when `denominator` is zero.
+    pub fn new(numerator: Integer, denominator: Integer) -> Option<Self> {
+        if denominator.is_zero() {
+            return None;
+        }
+
+        let gcd = numerator.gcd(&denominator);
+        let (mut numerator, mut denominator) = (numerator.checked_div(&gcd)?, denominator.checked_div(&gcd)?);
+        if denominator.is_negative() {
+            numerator = -&numerator;
+            denominator = -&denominator;
+        }
+
+        Some(Self { numerator, denominator })
+    }
+
+    /// The exact value of `f`, `None` for infinities and NaN.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+
+        // every finite float is an integer mantissa times a power of two
+        let bits = f.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let mantissa = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = match exponent {
+            0 => (mantissa, -1074),
+            _ => (mantissa | (1 << 52), exponent - 1075),
+        };
+
+        let mantissa = Integer::from(if f < 0.0 { -(mantissa as i128) } else { mantissa as i128 });
+        let power = Integer::from(2).checked_pow(&Integer::from(exponent.unsigned_abs() as i128))?;
+        if exponent < 0 {
+            Self::new(mantissa, power)
+        } else {
+            Self::new(mantissa.checked_mul(&power)?, Integer::from(1))
+        }
+    }
+
+    pub fn get_numerator(&self) -> &Integer {
+        &self.numerator
+    }
+
+    pub fn get_denominator(&self) -> &Integer {
+        &self.denominator
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.numerator.is_negative()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // shift wide operands down first so neither side turns into an infinity
+        let shift = self.numerator.bits().max(self.denominator.bits()).saturating_sub(1000);
+        let scale = Integer::from(2).checked_pow(&Integer::from(shift as i128)).unwrap_or(Integer::from(1));
+        let numerator = self.numerator.checked_div(&scale).unwrap_or(Integer::from(0));
+        let denominator = self.denominator.checked_div(&scale).unwrap_or(Integer::from(1));
+
+        if denominator.is_zero() {
+            numerator.to_f64() * f64::INFINITY
+        } else {
+            numerator.to_f64() / denominator.to_f64()
+        }
+    }
+
+    /// Rounds towards zero.
+    pub fn trunc(&self) -> Integer {
+        self.numerator.checked_div(&self.denominator).unwrap_or(Integer::from(0))
+    }
+
+    pub fn neg(&self) -> Self {
+        Self { numerator: -&self.numerator, denominator: self.denominator.clone() }
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let numerator = &self.numerator.checked_mul(&other.denominator)? + &other.numerator.checked_mul(&self.denominator)?;
+        Self::new(numerator, self.denominator.checked_mul(&other.denominator)?)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.neg())
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Self::new(self.numerator.checked_mul(&other.numerator)?, self.denominator.checked_mul(&other.denominator)?)
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        Self::new(self.numerator.checked_mul(&other.denominator)?, self.denominator.checked_mul(&other.numerator)?)
+    }
+
+    /// Remainder of the division truncated towards zero, it has the sign of `self`.
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        let quotient = Self::from(self.checked_div(other)?.trunc());
+        self.checked_sub(&other.checked_mul(&quotient)?)
+    }
+
+    /// A negative `exp` inverts the result, `None` for zero raised to a negative power.
+    pub fn checked_pow(&self, exp: &Integer) -> Option<Self> {
+        let numerator = self.numerator.checked_pow(&exp.abs())?;
+        let denominator = self.denominator.checked_pow(&exp.abs())?;
+
+        if exp.is_negative() {
+            Self::new(denominator, numerator)
+        } else {
+            Self::new(numerator, denominator)
+        }
+    }
+}
+
+impl From<Integer> for Rational {
+    fn from(value: Integer) -> Self {
+        Self { numerator: value, denominator: Integer::from(1) }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are positive, so cross multiplying keeps the order
+        let a = self.numerator.checked_mul(&other.denominator);
+        let b = other.numerator.checked_mul(&self.denominator);
+
+        match (a, b) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::builtin::Builtin;
+use crate::error_code::{CodedError, ErrorCode};
+use crate::highlight;
+use crate::interpreter::Interpreter;
+use crate::keyword::Keyword;
+use crate::lexer::Lexer;
+use crate::line_editor::LineEditor;
+use crate::parser::Parser;
+use crate::signal;
+use crate::token::TT;
+
+const HELP: &str = "\
+:help            show this list
+:vars            list the variables of the session
+:type <expr>     show the type of an expression, the session isn\'t changed
+:ast <input>     show the node tree input parses to
+:tokens <input>  show the tokens input lexes to
+:reset           forget every variable of the session
+:load <file>     run a file in the session
+:quit            end the session, so does Ctrl-D";
+
+/// The interactive session `kumi` starts without a file.
+pub fn run(deny_warnings: bool) {
+    let mut interpreter = Interpreter::new("<stdin>", "").expect("error creating interpreter");
+    interpreter.set_interactive(true);
+    interpreter.set_deny_warnings(deny_warnings);
+    let colors = io::stdin().is_terminal() && io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let mut editor = LineEditor::new(colors);
+    signal::catch_interrupts();
+    // lines of a statement that isn't complete yet
+    let mut pending = String::new();
+    loop {
+        let prompt = if pending.is_empty() { "kumi> " } else { "...> " };
+        let line = match editor.read_line(prompt, &pending, &completions(&interpreter)) {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                pending.clear();
+                continue
+            },
+            Err(err) => {
+                eprintln!("can\'t read input: {}", err);
+                break
+            },
+        };
+        signal::clear_interrupt();
+
+        if let Some(command) = line.trim().strip_prefix(':').filter(|_| pending.is_empty()) {
+            if command.trim() == "quit" {
+                break
+            }
+            if let Err(err) = run_command(&mut interpreter, command, colors) {
+                println!("{}", err)
+            }
+            continue
+        }
+
+        pending += &line;
+        if is_incomplete(&pending) {
+            pending.push('\n');
+            continue
+        }
+        let input = std::mem::take(&mut pending);
+        let input = input.trim();
+
+        if let Err(err) = interpreter.update(input) {
+            println!("{}", err);
+            continue
+        };
+
+        let result = match interpreter.run() {
+            Ok(result) => result,
+            Err(err) => {
+                println!("{}", err);
+                continue
+            },
+        };
+
+        print_value(&format!("{:?}", result), colors)
+    }
+}
+
+/// Whether `text` stops inside a statement: a string, a comment or brackets are still open, or an operator
+/// waits for its operand. The lexer and parser fail at the very end of such text.
+///
+/// It's checked with the new line the next line would add, so input that can't go on over a new line, like
+/// `let x` without its `=`, is reported right away, while `let x =` waits for the value like `1 +` does.
+fn is_incomplete(text: &str) -> bool {
+    let tokens = match Lexer::new(&format!("{}\n", text)).try_tokenize() {
+        Ok(tokens) => tokens,
+        Err((_, err)) => return CodedError::of(&err).is_some_and(|err| matches!(
+            err.get_code(),
+            ErrorCode::UnterminatedString | ErrorCode::UnterminatedComment
+        )),
+    };
+
+    Parser::new(&tokens).parse().is_err_and(|err| matches!(err.get_token().get_tt(), TT::EOF))
+}
+
+/// Words tab completes: keywords, builtins and the variables of the session.
+fn completions(interpreter: &Interpreter) -> Vec<String> {
+    let mut words: Vec<String> = Keyword::hash_map().into_keys().collect();
+    words.extend(["true", "false", "none"].map(String::from));
+    words.extend(Builtin::builtins().iter().map(|builtin| builtin.get_name().to_string()));
+    words.extend(interpreter.get_variables().iter().map(|variable| variable.get_name().to_string()));
+
+    words
+}
+
+fn print_value(value: &str, colors: bool) {
+    println!("{}", if colors { highlight::colorize(value) } else { value.to_string() })
+}
+
+/// Runs the meta-command `command`, the input after its `:`.
+fn run_command(interpreter: &mut Interpreter, command: &str, colors: bool) -> io::Result<()> {
+    let (name, argument) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    let argument = argument.trim();
+
+    match name {
+        "help" => println!("{}", HELP),
+        "vars" => for variable in interpreter.get_variables() {
+            print_value(&format!("{} = {:?}", variable.get_name(), variable.get_value()), colors)
+        },
+        "type" => println!("{}", interpreter.evaluate_isolated(argument)?.get_type_name()),
+        "ast" => {
+            let tokens = Lexer::new(argument).tokenize()?;
+            let node = Parser::new(&tokens).parse().map_err(|err| err.format(argument))?;
+            print!("{:?}", node)
+        },
+        "tokens" => for token in Lexer::new(argument).tokenize()? {
+            println!("{}..{} {:?}", token.get_start(), token.get_end(), token.get_tt())
+        },
+        "reset" => interpreter.reset(),
+        "load" if argument.is_empty() => println!("usage: :load <file>"),
+        "load" => {
+            let result = interpreter.load(argument)?;
+            print_value(&format!("{:?}", result), colors)
+        },
+        _ => println!("unknown command :{}, :help lists the commands", name),
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use std::io;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error_code::ErrorCode;
+
+const SIGINT: c_int = 2;
+
+/// Set by Ctrl-C once [`catch_interrupts`] was called, until evaluation notices it.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" {
+    fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+}
+
+extern "C" fn on_interrupt(_: c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst)
+}
+
+/// Makes Ctrl-C stop the running evaluation instead of the whole process.
+pub fn catch_interrupts() {
+    // SAFETY: the handler only stores to an atomic, which is safe to do inside a signal handler
+    unsafe {
+        signal(SIGINT, on_interrupt);
+    }
+}
+
+/// Forgets a Ctrl-C pressed while nothing was evaluated.
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst)
+}
+
+/// Fails once after Ctrl-C was pressed, loops and calls check it so long evaluations can be stopped.
+pub fn check_interrupt() -> io::Result<()> {
+    if INTERRUPTED.swap(false, Ordering::SeqCst) {
+        return Err(ErrorCode::Interrupted.error("interrupted by Ctrl-C"));
+    }
+
+    Ok(())
+}
\ No newline at end of file
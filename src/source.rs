@@ -0,0 +1,19 @@
+/// Text of a program together with the name it's reported under in tracebacks.
+pub struct Source {
+    name: String,
+    text: Vec<char>
+}
+
+impl Source {
+    pub fn new(name: &str, text: &str) -> Self {
+        Self { name: name.to_string(), text: text.chars().collect() }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_text(&self) -> &[char] {
+        &self.text
+    }
+}
\ No newline at end of file
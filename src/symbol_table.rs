@@ -1,42 +1,70 @@
 use std::collections::HashMap;
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use crate::error_code::ErrorCode;
 use crate::variable::Variable;
 
+/// Source of the ids telling scopes apart.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Clone)]
 pub struct SymbolTable<'p> {
+    /// Identifies the scope, functions remember the one they were declared in.
+    id: usize,
     parent: Option<&'p Self>,
     symbols: HashMap<String, Variable>
 }
 
+impl Default for SymbolTable<'_> {
+    fn default() -> Self {
+        Self { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), parent: None, symbols: HashMap::new() }
+    }
+}
+
 impl<'p> SymbolTable<'p> {
     pub fn new(parent: &'p Self) -> Self {
-        Self { parent: Some(parent), symbols: HashMap::new() }
+        Self { parent: Some(parent), ..Self::default() }
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    /// The scope with `id` among this one and its parents.
+    pub fn find(&self, id: usize) -> Option<&Self> {
+        let mut table = Some(self);
+        while let Some(current) = table {
+            if current.id == id {
+                return Some(current);
+            }
+            table = current.parent
+        }
+
+        None
     }
 
     pub fn get_from_tree(&self, name: &str) -> io::Result<&Variable> {    
         let result = self.symbols.get(name);
 
-        result.map_or(self.parent.map_or(Err(io::Error::new(
-            io::ErrorKind::NotFound,
+        result.map_or(self.parent.map_or(Err(ErrorCode::UndefinedVariable.error(
             format!("there isn\'t variable with name {}", name)
-        )), |result| result.get_from_tree(name)), |variable| Ok(variable))
+        )), |result| result.get_from_tree(name)), Ok)
+    }
+
+    /// Variables declared in this table itself, not in its parents.
+    pub fn get_variables(&self) -> Vec<&Variable> {
+        self.symbols.values().collect()
     }
 
     pub fn declare(&mut self, variable: Variable) {
         self.symbols.insert(variable.get_name().to_string(), variable);
     }
 
+    #[allow(dead_code)]
     pub fn remove(&mut self, name: &str) -> io::Result<()> {
-        self.symbols.remove(name).map_or(Err(io::Error::new(
-            io::ErrorKind::NotFound,
+        self.symbols.remove(name).map_or(Err(ErrorCode::UndefinedVariable.error(
             format!("there isn\'t variable with name {}", name)
         )), |_| Ok(()))
     }
-}
-
-impl<'p> Default for SymbolTable<'p> {
-    fn default() -> Self {
-        Self { parent: None, symbols: HashMap::new() }
-    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 use std::io;
 
+use crate::error_code::ErrorCode;
 use crate::keyword::Keyword;
 use crate::op::Op;
 use crate::types::Type;
@@ -34,11 +35,26 @@ impl Token {
 pub enum TT {
     RPR,
     LPR,
+    RBR,
+    LBR,
+    RSQ,
+    LSQ,
+    COMMA,
+    DOT,
+    QDOT,
+    DOTDOT,
+    DOTDOTEQ,
+    QUESTION,
+    COLON,
+    ARROW,
     Op(Op),
     Type(Type),
     Keyword(Keyword),
     Indenifier(String),
+    Doc(String),
     EQ,
+    NL,
+    SEMI,
     EOF
 }
 
@@ -48,10 +64,7 @@ impl TryInto<Type> for TT {
     fn try_into(self) -> Result<Type, Self::Error> {
         match self {
             TT::Type(type_) => Ok(type_),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t convert {} to type", self.to_string())
-            ))
+            _ => Err(ErrorCode::UnexpectedToken.error(format!("can\'t convert {} to type", self.to_string())))
         }
     }
 }
@@ -62,10 +75,7 @@ impl TryInto<Op> for TT {
     fn try_into(self) -> Result<Op, Self::Error> {
         match self {
             TT::Op(op) => Ok(op),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t convert {} to operator", self.to_string())
-            ))
+            _ => Err(ErrorCode::UnexpectedToken.error(format!("can\'t convert {} to operator", self.to_string())))
         }
     }
 }
@@ -75,11 +85,26 @@ impl ToString for TT {
         match self {
             TT::RPR => ")".to_string(),
             TT::LPR => "(".to_string(),
+            TT::RBR => "}".to_string(),
+            TT::LBR => "{".to_string(),
+            TT::RSQ => "]".to_string(),
+            TT::LSQ => "[".to_string(),
+            TT::COMMA => ",".to_string(),
+            TT::DOT => ".".to_string(),
+            TT::QDOT => "?.".to_string(),
+            TT::DOTDOT => "..".to_string(),
+            TT::DOTDOTEQ => "..=".to_string(),
+            TT::QUESTION => "?".to_string(),
+            TT::COLON => ":".to_string(),
+            TT::ARROW => "=>".to_string(),
             TT::Op(op) => op.to_string(),
             TT::Type(type_) => type_.to_string(),
             TT::Keyword(keyword) => keyword.to_string(),
             TT::Indenifier(indenifier) =>  indenifier.clone(),
+            TT::Doc(_) => "doc comment".to_string(),
             TT::EQ => "=".to_string(),
+            TT::NL => "new line".to_string(),
+            TT::SEMI => ";".to_string(),
             TT::EOF => "EOF".to_string(),
         }
     }
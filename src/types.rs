@@ -1,10 +1,29 @@
-use std::{fmt::Debug, io};
+use std::{cmp::Ordering, fmt::Debug, io, rc::Rc};
+
+use crate::builtin::Builtin;
+use crate::decimal::Decimal;
+use crate::error_code::ErrorCode;
+use crate::error_value::ErrorValue;
+use crate::function::Function;
+use crate::integer::{Integer, MAX_BITS};
+use crate::range::Range;
+use crate::rational::Rational;
 
 #[derive(Clone)]
 pub enum Type {
-    Int(i128),
+    Int(Integer),
     Float(f64),
+    Rational(Rational),
+    Decimal(Decimal),
     Bool(bool),
+    Str(String),
+    Function(Rc<Function>),
+    Builtin(Builtin),
+    Error(Rc<ErrorValue>),
+    Ok(Box<Type>),
+    Err(Box<Type>),
+    Range(Rc<Range>),
+    List(Rc<Vec<Type>>),
     None
 }
 
@@ -13,162 +32,452 @@ impl Type {
         match self {
             Type::Int(i) => Ok(Type::Int(-i)),
             Type::Float(f) => Ok(Type::Float(-f)),
+            Type::Rational(r) => Ok(Type::Rational(r.neg())),
+            Type::Decimal(d) => Ok(Type::Decimal(d.neg())),
             Type::Bool(b) => Ok(Type::Bool(!b)),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t invert {}", self.to_string())
-            ))
+            _ => Err(ErrorCode::BadUnaryOperand.error(format!("can\'t invert {}", self.to_string())))
         }
     }
 
-    pub fn ensure_float<F>(&self, token: &Self, func: F) -> Option<Self> where F: FnOnce(&f64, &f64) -> Self {
+    pub fn ensure_bool<F>(&self, token: &Self, func: F) -> Option<Type> where F: FnOnce(&bool, &bool) -> Self {
         match self {
-            Type::Float(a) => match token {
-                Type::Float(b) => Some(func(a, b)),
+            Type::Bool(a) => match token {
+                Type::Bool(b) => Some(func(a, b)),
                 _ => None
             },
             _ => None
         }
     }
 
-    pub fn ensure_int<F>(&self, token: &Self, func: F) -> Option<Self> where F: FnOnce(&i128, &i128) -> Self {
+    /// Position in the numeric tower, an operation promotes both numbers to the higher level.
+    ///
+    /// Ints, decimals and rationals are exact and each one can hold every value of the previous level,
+    /// a float makes the whole operation approximate.
+    fn level(&self) -> Option<u8> {
         match self {
-            Type::Int(a) => match token {
-                Type::Int(b) => Some(func(a, b)),
-                _ => None
-            },
+            Type::Int(_) => Some(0),
+            Type::Decimal(_) => Some(1),
+            Type::Rational(_) => Some(2),
+            Type::Float(_) => Some(3),
             _ => None
         }
     }
 
-    pub fn ensure_bool<F>(&self, token: &Self, func: F) -> Option<Type> where F: FnOnce(&bool, &bool) -> Self {
+    fn promoted(&self, level: u8) -> Self {
+        match (self, level) {
+            (Type::Int(i), 1) => Type::Decimal(Decimal::from(i.clone())),
+            (Type::Int(i), 2) => Type::Rational(Rational::from(i.clone())),
+            (Type::Decimal(d), 2) => d.to_rational().map_or(Type::Float(d.to_f64()), Type::Rational),
+            (_, 3) => self.to_f64().map_or(Type::None, Type::Float),
+            _ => self.clone()
+        }
+    }
+
+    /// Brings two numbers to a common type following [`Self::level`], an int meeting a float becomes a float.
+    pub fn promote(&self, other: &Self) -> (Self, Self) {
+        match (self.level(), other.level()) {
+            (Some(a), Some(b)) if a != b => (self.promoted(a.max(b)), other.promoted(a.max(b))),
+            _ => (self.clone(), other.clone())
+        }
+    }
+
+    /// The value of a number as a float, `None` for other types.
+    pub fn to_f64(&self) -> Option<f64> {
         match self {
-            Type::Bool(a) => match token {
-                Type::Bool(b) => Some(func(a, b)),
-                _ => None
-            },
+            Type::Int(i) => Some(i.to_f64()),
+            Type::Float(f) => Some(*f),
+            Type::Rational(r) => Some(r.to_f64()),
+            Type::Decimal(d) => Some(d.to_f64()),
             _ => None
         }
     }
 
     pub fn add(&self, other: &Self) -> io::Result<Self> {
-        let number = match (self.ensure_float(other, |a, b| Type::Float(a+b)), self.ensure_int(other, |a, b| Type::Int(a+b))) {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute add operation on {} and {}, the types must match", self.to_string(), other.to_string()
-            )))
-        };
-        
-        Ok(number)
+        match self.promote(other) {
+            (Type::Int(a), Type::Int(b)) => Ok(Type::Int(&a + &b)),
+            (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a + b)),
+            (Type::Str(a), Type::Str(b)) => Ok(Type::Str(a + &b)),
+            (Type::Rational(a), Type::Rational(b)) => a.checked_add(&b).map(Type::Rational).ok_or_else(|| self.overflow("+", other)),
+            (Type::Decimal(a), Type::Decimal(b)) => a.checked_add(&b).map(Type::Decimal).ok_or_else(|| self.overflow("+", other)),
+            _ => Err(self.mismatch("add", other))
+        }
     }
 
     pub fn sub(&self, other: &Self) -> io::Result<Self> {
-        let number = match (self.ensure_float(other, |a, b| Type::Float(a-b)), self.ensure_int(other, |a, b| Type::Int(a-b))) {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute subtract operation on {} and {}, the types must match", self.to_string(), other.to_string()
-            )))
-        };
-        
-        Ok(number)
+        match self.promote(other) {
+            (Type::Int(a), Type::Int(b)) => Ok(Type::Int(&a - &b)),
+            (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a - b)),
+            (Type::Rational(a), Type::Rational(b)) => a.checked_sub(&b).map(Type::Rational).ok_or_else(|| self.overflow("-", other)),
+            (Type::Decimal(a), Type::Decimal(b)) => a.checked_sub(&b).map(Type::Decimal).ok_or_else(|| self.overflow("-", other)),
+            _ => Err(self.mismatch("subtract", other))
+        }
     }
 
+    /// Ints are divided truncating towards zero, write `1/3r` for an exact fraction.
     pub fn devd(&self, other: &Self) -> io::Result<Self> {
         if other.is_zero() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t devide {:?} by zero ({:?})", self, other)
-            ));
+            return Err(ErrorCode::DivisionByZero.error(format!("can\'t devide {:?} by zero ({:?})", self, other)));
+        }
+
+        match self.promote(other) {
+            (Type::Int(a), Type::Int(b)) => a.checked_div(&b).map(Type::Int).ok_or_else(|| self.overflow("/", other)),
+            (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a / b)),
+            (Type::Rational(a), Type::Rational(b)) => a.checked_div(&b).map(Type::Rational).ok_or_else(|| self.overflow("/", other)),
+            (Type::Decimal(a), Type::Decimal(b)) => a.checked_div(&b).map(Type::Decimal).ok_or_else(|| self.overflow("/", other)),
+            _ => Err(self.mismatch("devide", other))
         }
-        let result = (self.ensure_float(other, |a, b| Type::Float(a/b)), self.ensure_int(other, |a, b| Type::Int(a/b)));
-    
-        let number = match result {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute devide operation on {} and {}, the types must match", self.to_string(), other.to_string()
-            )))
-        };
-        
-        Ok(number)
     }
 
     pub fn mult(&self, other: &Self) -> io::Result<Self> {
-        let number = match (self.ensure_float(other, |a, b| Type::Float(a*b)), self.ensure_int(other, |a, b| Type::Int(a*b))) {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute multiply operation on {} and {}, the types must match", self.to_string(), other.to_string()
-            )))
-        };
-        
-        Ok(number)
+        match self.promote(other) {
+            (Type::Int(a), Type::Int(b)) => a.checked_mul(&b).map(Type::Int).ok_or_else(|| self.overflow("*", other)),
+            (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a * b)),
+            (Type::Rational(a), Type::Rational(b)) => a.checked_mul(&b).map(Type::Rational).ok_or_else(|| self.overflow("*", other)),
+            (Type::Decimal(a), Type::Decimal(b)) => a.checked_mul(&b).map(Type::Decimal).ok_or_else(|| self.overflow("*", other)),
+            _ => Err(self.mismatch("multiply", other))
+        }
     }
 
+    /// An int exponent keeps exact numbers exact, except that a negative power of an int is a float.
+    /// Any other exponent makes the result a float.
     pub fn pow(&self, other: &Self) -> io::Result<Self> {
-        let number = match (self.ensure_float(other, |a, b| Type::Float(a.powf(*b))), self.ensure_int(other, |a, b| Type::Int(a.pow(*b as u32)))) {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute power operation on {} and {}, the types must match", self.to_string(), other.to_string()
-            )))
-        };
-        
-        Ok(number)
+        if let Type::Int(exp) = other {
+            if exp.is_negative() && self.is_zero() && !matches!(self, Type::Float(_)) {
+                return Err(ErrorCode::DivisionByZero.error(format!("can\'t raise {:?} to a negative power ({:?})", self, other)));
+            }
+
+            match self {
+                Type::Int(base) if exp.is_negative() => return Type::Float(base.to_f64()).pow(&Type::Float(exp.to_f64())),
+                Type::Int(base) => return base.checked_pow(exp).map(Type::Int).ok_or_else(|| self.overflow("^", other)),
+                Type::Rational(base) => return base.checked_pow(exp).map(Type::Rational).ok_or_else(|| self.overflow("^", other)),
+                Type::Decimal(base) => return base.checked_pow(exp).map(Type::Decimal).ok_or_else(|| self.overflow("^", other)),
+                _ => {}
+            }
+        }
+
+        match (self.to_f64(), other.to_f64()) {
+            (Some(a), Some(b)) => Ok(Type::Float(a.powf(b))),
+            _ => Err(self.mismatch("power", other))
+        }
     }
 
+    /// The remainder has the sign of `self`, `-7 % 2` is `-1`, so it doesn't pair up with `//` for negative ints.
     pub fn remain(&self, other: &Self) -> io::Result<Self> {
-        let number = match (self.ensure_float(other, |a, b| Type::Float(a%b)), self.ensure_int(other, |a, b| Type::Int(a%b))) {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute remainder operation on {} and {}, the types must match", self.to_string(), other.to_string()
-            )))
-        };
-        
-        Ok(number)
+        if other.is_zero() {
+            return Err(ErrorCode::DivisionByZero.error(format!("can\'t take remainder of {:?} by zero ({:?})", self, other)));
+        }
+
+        match self.promote(other) {
+            (Type::Int(a), Type::Int(b)) => a.checked_rem(&b).map(Type::Int).ok_or_else(|| self.overflow("%", other)),
+            (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a % b)),
+            (Type::Rational(a), Type::Rational(b)) => a.checked_rem(&b).map(Type::Rational).ok_or_else(|| self.overflow("%", other)),
+            (Type::Decimal(a), Type::Decimal(b)) => a.checked_rem(&b).map(Type::Decimal).ok_or_else(|| self.overflow("%", other)),
+            _ => Err(self.mismatch("remainder", other))
+        }
     }
 
     pub fn eq(&self, other: &Self) -> io::Result<Self> {
-        let number = match (self.ensure_float(other, |a, b| Type::Bool(a==b)), self.ensure_int(other, |a, b| Type::Bool(a==b))) {
-            (None, Some(i)) => i,
-            (Some(f), None) => f,
-            _ => return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("can\'t execute equals operation on {} and {}, the types must match", self.to_string(), other.to_string()
+        Ok(Type::Bool(self.equals(other)))
+    }
+
+    pub fn lt(&self, other: &Self) -> io::Result<Self> {
+        Ok(Type::Bool(self.ordering(other, "less than")? == Some(Ordering::Less)))
+    }
+
+    pub fn lte(&self, other: &Self) -> io::Result<Self> {
+        Ok(Type::Bool(matches!(self.ordering(other, "less or equal")?, Some(Ordering::Less | Ordering::Equal))))
+    }
+
+    pub fn gt(&self, other: &Self) -> io::Result<Self> {
+        Ok(Type::Bool(self.ordering(other, "greater than")? == Some(Ordering::Greater)))
+    }
+
+    pub fn gte(&self, other: &Self) -> io::Result<Self> {
+        Ok(Type::Bool(matches!(self.ordering(other, "greater or equal")?, Some(Ordering::Greater | Ordering::Equal))))
+    }
+
+    /// How `==`, `!=`, `<`, `<=`, `>` and `>=` treat every type of value.
+    ///
+    /// - Numbers compare by value whatever their types, so `1 == 1.0` and `1/2r == 0.5d`. A NaN float
+    ///   isn't equal to anything, itself included, and isn't ordered.
+    /// - Bools, strings and `none` are equal when they hold the same value. Strings are ordered
+    ///   char by char and `false < true`, `none` isn't ordered.
+    /// - Functions are equal only to themselves and builtins only to the same builtin, neither is ordered.
+    /// - Errors are equal when their kind, message, code and span are, they aren't ordered.
+    /// - `ok(a) == ok(b)` and `err(a) == err(b)` when `a == b`, results aren't ordered.
+    /// - Values of different types are never equal, `1 == true` is `false` rather than an error,
+    ///   but ordering them is a type error.
+    /// - Lists compare element by element with these same rules and are ordered like words in a
+    ///   dictionary, `[1, 2] < [1, 3]` and `[1] < [1, 0]`. Ranges are equal when they produce the same
+    ///   ints, `0..3 == 0..=2`, and aren't ordered.
+    pub fn equals(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Type::Str(a), Type::Str(b)) => a == b,
+            (Type::Bool(a), Type::Bool(b)) => a == b,
+            (Type::None, Type::None) => true,
+            (Type::Function(a), Type::Function(b)) => Rc::ptr_eq(a, b),
+            (Type::Builtin(a), Type::Builtin(b)) => a == b,
+            (Type::Error(a), Type::Error(b)) => a == b,
+            (Type::Ok(a), Type::Ok(b)) | (Type::Err(a), Type::Err(b)) => a.equals(b),
+            (Type::Range(a), Type::Range(b)) => a == b,
+            (Type::List(a), Type::List(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.equals(b)),
+            _ => matches!(self.ordering(other, "equals"), Ok(Some(Ordering::Equal)))
+        }
+    }
+
+    /// `None` means the values are unordered, which happens only with a NaN float.
+    /// See [`Self::equals`] for the rules.
+    fn ordering(&self, other: &Self, operation: &str) -> io::Result<Option<Ordering>> {
+        match self.promote(other) {
+            (Type::Int(a), Type::Int(b)) => Ok(a.partial_cmp(&b)),
+            (Type::Float(a), Type::Float(b)) => Ok(a.partial_cmp(&b)),
+            (Type::Rational(a), Type::Rational(b)) => Ok(a.partial_cmp(&b)),
+            (Type::Decimal(a), Type::Decimal(b)) => Ok(a.partial_cmp(&b)),
+            (Type::Str(a), Type::Str(b)) => Ok(a.partial_cmp(&b)),
+            (Type::Bool(a), Type::Bool(b)) => Ok(a.partial_cmp(&b)),
+            (Type::List(a), Type::List(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match a.ordering(b, operation)? {
+                        Some(Ordering::Equal) => {},
+                        ordering => return Ok(ordering),
+                    }
+                }
+
+                Ok(a.len().partial_cmp(&b.len()))
+            },
+            _ => Err(ErrorCode::TypeMismatch.error(format!(
+                "can\'t execute {} operation on {} and {}, only numbers, strings, bools and lists are ordered and only among themselves",
+                operation, self.to_string(), other.to_string()
             )))
-        };
-        
-        Ok(number)
+        }
+    }
+
+    /// Division of ints rounding towards negative infinity, `-7 // 2` is `-4`, unlike `%` that truncates.
+    pub fn floor_devd(&self, other: &Self) -> io::Result<Self> {
+        self.int_op(other, "floor devide", |a, b| a.checked_floor_div(b).ok_or_else(
+            || ErrorCode::DivisionByZero.error(format!("can\'t devide {:?} by zero ({:?})", self, other))
+        ))
+    }
+
+    pub fn bit_and(&self, other: &Self) -> io::Result<Self> {
+        self.int_op(other, "bitwise and", |a, b| Ok(a.bit_and(b)))
+    }
+
+    pub fn bit_or(&self, other: &Self) -> io::Result<Self> {
+        self.int_op(other, "bitwise or", |a, b| Ok(a.bit_or(b)))
+    }
+
+    pub fn xor(&self, other: &Self) -> io::Result<Self> {
+        self.int_op(other, "xor", |a, b| Ok(a.bit_xor(b)))
+    }
+
+    pub fn shl(&self, other: &Self) -> io::Result<Self> {
+        self.int_op(other, "left shift", |a, b| a.checked_shl(self.shift_count(b)?).ok_or_else(|| self.overflow("<<", other)))
+    }
+
+    pub fn shr(&self, other: &Self) -> io::Result<Self> {
+        self.int_op(other, "right shift", |a, b| Ok(a.shr(self.shift_count(b)?)))
+    }
+
+    fn shift_count(&self, count: &Integer) -> io::Result<u64> {
+        if count.is_negative() {
+            return Err(ErrorCode::NegativeShift.error(format!("can\'t shift {} by a negative count ({})", self.to_string(), count)));
+        }
+
+        // shifting by more than fits into u64 bits overflows or clears the int anyway
+        Ok(count.to_i128().and_then(|count| u64::try_from(count).ok()).unwrap_or(u64::MAX))
+    }
+
+    pub fn bit_not(&self) -> io::Result<Self> {
+        match self {
+            Type::Int(i) => Ok(Type::Int(i.bit_not())),
+            _ => Err(ErrorCode::BadUnaryOperand.error(format!("can\'t execute bitwise not on {}, only ints support it", self.to_string())))
+        }
+    }
+
+    /// Operations defined for ints only, other numbers aren't converted.
+    fn int_op<F>(&self, other: &Self, operation: &str, func: F) -> io::Result<Self> where F: FnOnce(&Integer, &Integer) -> io::Result<Integer> {
+        match (self, other) {
+            (Type::Int(a), Type::Int(b)) => Ok(Type::Int(func(a, b)?)),
+            _ => Err(ErrorCode::TypeMismatch.error(
+                format!("can\'t execute {} operation on {} and {}, both operands must be ints", operation, self.to_string(), other.to_string())
+            ))
+        }
     }
 
     pub fn and(&self, other: &Self) -> io::Result<Self> {
-        other.ensure_bool(other, |a, b| Type::Bool(*a && *b)).map_or(Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("can\'t execute and operation on {} and {}, the types must match", self.to_string(), other.to_string()
-        ))), |out| Ok(out))
+        self.ensure_bool(other, |a, b| Type::Bool(*a && *b)).ok_or_else(|| ErrorCode::TypeMismatch.error(
+            format!("can\'t execute and operation on {} and {}, both operands must be bools", self.to_string(), other.to_string()
+        )))
     }
 
     pub fn or(&self, other: &Self) -> io::Result<Self> {
-        other.ensure_bool(other, |a, b| Type::Bool(*a || *b)).map_or(Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("can\'t execute or operation on {} and {}, the types must match", self.to_string(), other.to_string()
-        ))), |out| Ok(out))
+        self.ensure_bool(other, |a, b| Type::Bool(*a || *b)).ok_or_else(|| ErrorCode::TypeMismatch.error(
+            format!("can\'t execute or operation on {} and {}, both operands must be bools", self.to_string(), other.to_string()
+        )))
+    }
+
+    /// Reads `self.name`, error values and functions have members.
+    pub fn get_member(&self, name: &str) -> io::Result<Self> {
+        let member = match (self, name) {
+            (Type::Error(error), _) => error.get_member(name),
+            (Type::Function(function), "name") => Some(Type::Str(function.get_name().to_string())),
+            (Type::Function(function), "doc") => Some(function.get_doc().map_or(Type::None, |doc| Type::Str(doc.to_string()))),
+            _ => None
+        };
+
+        member.ok_or_else(|| ErrorCode::UnknownMember.error(format!("{} has no member {}", self.to_string(), name)))
+    }
+
+    /// The int a number is equal to, `None` for fractions and other types.
+    pub fn as_integer(&self) -> Option<Integer> {
+        let integer = match self {
+            Type::Int(i) => return Some(i.clone()),
+            Type::Float(f) => Integer::from_f64(*f)?,
+            Type::Rational(r) => r.trunc(),
+            Type::Decimal(d) => d.trunc()?,
+            _ => return None
+        };
+
+        Type::Int(integer.clone()).equals(self).then_some(integer)
+    }
+
+    /// `value in self`, ranges hold ints, lists any values and strings the strings inside them.
+    pub fn contains(&self, value: &Self) -> io::Result<bool> {
+        match (self, value) {
+            (Type::Range(range), _) => Ok(value.as_integer().is_some_and(|value| range.contains(&value))),
+            (Type::List(items), _) => Ok(items.iter().any(|item| item.equals(value))),
+            (Type::Str(s), Type::Str(part)) => Ok(s.contains(part.as_str())),
+            _ => Err(ErrorCode::TypeMismatch.error(format!(
+                "can\'t look for {} in {}, only ranges, lists and strings can be searched", value.to_string(), self.to_string()
+            )))
+        }
+    }
+
+    /// Elements visited by `for`, a range computes them as they're needed.
+    pub fn iter(&self) -> io::Result<Box<dyn Iterator<Item = Type> + '_>> {
+        match self {
+            Type::Range(range) => Ok(Box::new(range.iter().map(Type::Int))),
+            Type::List(items) => Ok(Box::new(items.iter().cloned())),
+            Type::Str(s) => Ok(Box::new(s.chars().map(|c| Type::Str(c.to_string())))),
+            _ => Err(ErrorCode::TypeMismatch.error(format!(
+                "can\'t iterate over {}, only ranges, lists and strings are iterable", self.to_string()
+            )))
+        }
     }
 
+    pub fn len(&self) -> io::Result<Integer> {
+        match self {
+            Type::Range(range) => Ok(range.len()),
+            Type::List(items) => Ok(Integer::from(items.len() as i128)),
+            Type::Str(s) => Ok(Integer::from(s.chars().count() as i128)),
+            _ => Err(ErrorCode::TypeMismatch.error(format!("{} has no length", self.to_string())))
+        }
+    }
+
+    /// `self[index]` for an int and `self[a..b]` for a range, negative ints count from the end.
+    pub fn index(&self, index: &Self) -> io::Result<Self> {
+        match (self, index) {
+            (Type::List(items), Type::Int(i)) => Ok(items[self.position(i, true, items.len())?].clone()),
+            (Type::List(items), Type::Range(range)) => range.iter()
+                .map(|i| self.position(&i, false, items.len()).map(|i| items[i].clone()))
+                .collect::<io::Result<Vec<_>>>()
+                .map(|items| Type::List(Rc::new(items))),
+            (Type::Str(s), Type::Int(i)) => {
+                let chars: Vec<char> = s.chars().collect();
+                Ok(Type::Str(chars[self.position(i, true, chars.len())?].to_string()))
+            },
+            (Type::Str(s), Type::Range(range)) => {
+                let chars: Vec<char> = s.chars().collect();
+                range.iter().map(|i| self.position(&i, false, chars.len()).map(|i| chars[i])).collect::<io::Result<String>>().map(Type::Str)
+            },
+            (Type::List(_) | Type::Str(_), _) => Err(ErrorCode::TypeMismatch.error(format!(
+                "can\'t index {} with {}, the index must be an int or a range", self.to_string(), index.to_string()
+            ))),
+            _ => Err(ErrorCode::TypeMismatch.error(format!(
+                "can\'t index {}, only lists and strings can be indexed", self.to_string()
+            )))
+        }
+    }
+
+    /// Checks `index` against `len`, with `from_end` a negative index counts back from the end.
+    fn position(&self, index: &Integer, from_end: bool, len: usize) -> io::Result<usize> {
+        let position = match index.is_negative() && from_end {
+            true => index + &Integer::from(len as i128),
+            false => index.clone(),
+        };
+
+        position.to_i128().filter(|position| (0..len as i128).contains(position)).map(|position| position as usize).ok_or_else(
+            || ErrorCode::IndexOutOfRange.error(format!(
+                "can\'t get position {} of a {} of length {}", index, if let Type::Str(_) = self { "string" } else { "list" }, len
+            ))
+        )
+    }
+
+    /// `self.name(args)`, ranges, lists and strings have `len()`, `rev()` and `contains(value)`.
+    pub fn call_method(&self, name: &str, args: Vec<Type>) -> io::Result<Self> {
+        let arity = match (self, name) {
+            (Type::Range(_) | Type::List(_) | Type::Str(_), "len" | "rev") => 0,
+            (Type::Range(_) | Type::List(_) | Type::Str(_), "contains") => 1,
+            _ => return Err(ErrorCode::UnknownMember.error(format!("{} has no method {}", self.to_string(), name))),
+        };
+
+        if args.len() != arity {
+            return Err(ErrorCode::ArgumentCount.error(
+                format!("{} takes {} argument(s) but {} were given", name, arity, args.len())
+            ));
+        }
+
+        match (self, name) {
+            (_, "len") => Ok(Type::Int(self.len()?)),
+            (_, "contains") => Ok(Type::Bool(self.contains(&args[0])?)),
+            (Type::Range(range), _) => range.rev().map(|range| Type::Range(Rc::new(range))).ok_or_else(
+                || ErrorCode::IntegerOverflow.error(format!("the last element of {} is too large to reverse", self.to_string()))
+            ),
+            (Type::List(items), _) => Ok(Type::List(Rc::new(items.iter().rev().cloned().collect()))),
+            (Type::Str(s), _) => Ok(Type::Str(s.chars().rev().collect())),
+            _ => Err(ErrorCode::UnknownMember.error(format!("{} has no method {}", self.to_string(), name)))
+        }
+    }
+
+    fn overflow(&self, operation: &str, other: &Self) -> io::Error {
+        ErrorCode::IntegerOverflow.error(format!(
+            "{} {} {} needs an int wider than {} bits", self.to_string(), operation, other.to_string(), MAX_BITS
+        ))
+    }
+
+    fn mismatch(&self, operation: &str, other: &Self) -> io::Error {
+        ErrorCode::TypeMismatch.error(
+            format!("can\'t execute {} operation on {} and {}, both operands must be numbers", operation, self.to_string(), other.to_string())
+        )
+    }
+
+    /// Name of the value's type as `kumi` shows it, `int` for `int(5)`.
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            Type::None => "none",
+            Type::Int(_) => "int",
+            Type::Bool(_) => "bool",
+            Type::Str(_) => "str",
+            Type::Float(_) => "float",
+            Type::Rational(_) => "rational",
+            Type::Decimal(_) => "decimal",
+            Type::Function(_) => "fn",
+            Type::Builtin(_) => "builtin",
+            Type::Error(_) => "error",
+            Type::Ok(_) => "ok",
+            Type::Err(_) => "err",
+            Type::Range(_) => "range",
+            Type::List(_) => "list",
+        }
+    }
 
     pub fn is_zero(&self) -> bool {
         match self {
-            Type::Int(i) => i == &0,
+            Type::Int(i) => i.is_zero(),
             Type::Float(f) => f == &0.0,
+            Type::Rational(r) => r.is_zero(),
+            Type::Decimal(d) => d.is_zero(),
             _ => false
         }
     }
@@ -177,10 +486,20 @@ impl Type {
 impl ToString for Type {
     fn to_string(&self) -> String {
         match self {
-            Type::None => "()".to_string(),
+            Type::None => "none".to_string(),
             Type::Int(i) => format!("int({})", i),
             Type::Bool(b) => format!("bool({})", b),
+            Type::Str(s) => format!("str({:?})", s),
             Type::Float(f) => format!("float({})", f),
+            Type::Rational(r) => format!("rational({})", r),
+            Type::Decimal(d) => format!("decimal({})", d),
+            Type::Function(function) => format!("fn({})", function.get_name()),
+            Type::Builtin(builtin) => format!("builtin({})", builtin.get_name()),
+            Type::Error(error) => format!("error({})", error.to_string()),
+            Type::Ok(value) => format!("ok({})", value.to_string()),
+            Type::Err(value) => format!("err({})", value.to_string()),
+            Type::Range(range) => format!("range({})", range),
+            Type::List(items) => format!("[{}]", items.iter().map(|item| item.to_string()).collect::<Vec<_>>().join(", ")),
         }
     }
 }
@@ -1,5 +1,4 @@
-use crate::{types::Type, node::Node, context::Context};
-use std::io;
+use crate::types::Type;
 
 #[derive(Clone)]
 pub struct Variable {
@@ -8,8 +7,8 @@ pub struct Variable {
 }
 
 impl Variable {
-    pub fn new(name: &str, value: Node, context: &mut Context) -> io::Result<Self> {
-        Ok(Self { value: value.get_value(context)?, name: name.to_string() })
+    pub fn new(name: &str, value: Type) -> Self {
+        Self { value, name: name.to_string() }
     }
 
     pub fn get_value(&self) -> &Type {
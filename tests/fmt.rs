@@ -0,0 +1,56 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Writes `text` to a file of its own in the temporary directory.
+fn write_file(name: &str, text: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("kumi-fmt-{}-{}.kumi", std::process::id(), name));
+    fs::write(&path, text).expect("can't write the test file");
+
+    path
+}
+
+fn kumi(args: &[&str], path: &PathBuf) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kumi")).args(args).arg(path).output().expect("can't run kumi")
+}
+
+#[test]
+fn check_succeeds_on_formatted_files() {
+    let path = write_file("formatted", "let x = 1 + 2\n");
+    let output = kumi(&["fmt", "--check"], &path);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn check_fails_on_unformatted_files_without_changing_them() {
+    let path = write_file("unformatted", "let x=1+2\n");
+    let output = kumi(&["fmt", "--check"], &path);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("would reformat {}\n", path.display()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "let x=1+2\n");
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn check_fails_on_files_that_do_not_parse() {
+    let path = write_file("broken", "let x = (1\n");
+    let output = kumi(&["fmt", "--check"], &path);
+
+    assert_eq!(output.status.code(), Some(1));
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn fmt_rewrites_unformatted_files() {
+    let path = write_file("rewritten", "let x=1+2\n");
+    let output = kumi(&["fmt"], &path);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "let x = 1 + 2\n");
+    fs::remove_file(path).ok();
+}
\ No newline at end of file